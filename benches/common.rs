@@ -0,0 +1,17 @@
+//! Helpers shared by the benchmarks.
+
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 20;
+
+// returns the fastest of several timed rounds, which is the least disturbed by noise
+pub fn measure(mut round: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            round();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
@@ -0,0 +1,96 @@
+//! Compares fibonacci bucket indexing over power-of-two capacities
+//! with the modulo indexing the tables used before.
+//!
+//! Run with `cargo bench --bench indexing`.
+
+mod common;
+
+use std::hint::black_box;
+use std::time::Duration;
+use common::measure;
+use hashtable_rs::hasher::DefaultHashBuilder;
+use hashtable_rs::open::{Doubling, GrowthPolicy, HashTable};
+
+const ENTRIES: u64 = 100_000;
+// long enough that no probe sequence overflows, so that both tables keep the capacity
+// they were created with and the timings differ by indexing alone
+const MAX_PROBE: usize = 64;
+
+// grows like the default policy but maps hashes to buckets by a 64-bit division
+struct Modulo;
+
+impl GrowthPolicy for Modulo {
+    fn next_capacity(&self, capacity: usize) -> usize {
+        capacity * 2
+    }
+
+    fn bucket_index(&self, hashed_key: u64, capacity: usize) -> usize {
+        (hashed_key % (capacity as u64)) as usize
+    }
+}
+
+fn report(name: &str, modulo: Duration, fibonacci: Duration) {
+    println!(
+        "{:<10} modulo: {:>10.3?}  fibonacci: {:>10.3?}  speedup: {:.2}x",
+        name,
+        modulo,
+        fibonacci,
+        modulo.as_secs_f64() / fibonacci.as_secs_f64(),
+    );
+}
+
+fn bench_bucket_index() {
+    let hashes: Vec<u64> = (0..ENTRIES).map(|i| i.wrapping_mul(0x2545_f491_4f6c_dd1d)).collect();
+    let capacity = black_box(1 << 16);
+    let fibonacci_policy = Doubling;
+
+    let modulo = measure(|| {
+        for &hash in &hashes {
+            black_box(Modulo.bucket_index(black_box(hash), capacity));
+        }
+    });
+    let fibonacci = measure(|| {
+        for &hash in &hashes {
+            black_box(fibonacci_policy.bucket_index(black_box(hash), capacity));
+        }
+    });
+    report("index", modulo, fibonacci);
+}
+
+fn fill<G: GrowthPolicy>(growth_policy: G) -> HashTable<u64, u64, DefaultHashBuilder, G> {
+    let mut hash_table = HashTable::with_capacity_hasher_and_growth_policy(ENTRIES as usize, DefaultHashBuilder::with_seed(0x5eed), growth_policy);
+    hash_table.set_max_probe(MAX_PROBE);
+    for i in 0..ENTRIES {
+        hash_table.insert(i, i);
+    }
+    hash_table
+}
+
+fn lookup_all<G: GrowthPolicy>(hash_table: &HashTable<u64, u64, DefaultHashBuilder, G>) {
+    for i in 0..ENTRIES {
+        black_box(hash_table.get(&black_box(i)));
+    }
+}
+
+fn bench_table() {
+    let modulo_table = fill(Modulo);
+    let fibonacci_table = fill(Doubling);
+    assert_eq!(modulo_table.capacity(), fibonacci_table.capacity(), "both tables must have as many buckets");
+
+    let modulo = measure(|| {
+        black_box(fill(Modulo));
+    });
+    let fibonacci = measure(|| {
+        black_box(fill(Doubling));
+    });
+    report("insert", modulo, fibonacci);
+
+    let modulo = measure(|| lookup_all(&modulo_table));
+    let fibonacci = measure(|| lookup_all(&fibonacci_table));
+    report("get", modulo, fibonacci);
+}
+
+fn main() {
+    bench_bucket_index();
+    bench_table();
+}
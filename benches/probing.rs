@@ -0,0 +1,123 @@
+//! Compares the probe strategies of the open-addressing table.
+//!
+//! Clustering shows as longer probe sequences. For each strategy, buckets are filled
+//! to fixed load factors the way the table probes them, and the mean and longest
+//! number of buckets probed by lookups of stored and of missing keys are reported,
+//! followed by insert and get timings of whole tables.
+//!
+//! Run with `cargo bench --bench probing`.
+
+mod common;
+
+use std::hint::black_box;
+use std::hash::BuildHasher;
+use common::measure;
+use hashtable_rs::hasher::DefaultHashBuilder;
+use hashtable_rs::open::{DoubleHashing, Doubling, GrowthPolicy, HashTable, Linear, ProbeStrategy, Quadratic};
+
+const ENTRIES: u64 = 100_000;
+// long enough for the strategies, rather than the default limit, to decide when tables rehash
+const MAX_PROBE: usize = 32;
+const CAPACITY: usize = 1 << 17;
+const LOAD_FACTORS: [f64; 3] = [0.5, 0.75, 0.9];
+
+#[derive(Default)]
+struct ProbeLengths {
+    total: usize,
+    max: usize,
+    lookups: usize,
+}
+
+impl ProbeLengths {
+    fn record(&mut self, probes: usize) {
+        self.total += probes;
+        self.max = self.max.max(probes);
+        self.lookups += 1;
+    }
+
+    fn mean(&self) -> f64 {
+        self.total as f64 / self.lookups as f64
+    }
+}
+
+// fills CAPACITY buckets to the load factor without a maximum probe length,
+// returning the probe lengths of lookups of every stored key and of as many missing ones
+fn probe_lengths<P: ProbeStrategy>(probe_strategy: &P, load_factor: f64) -> (ProbeLengths, ProbeLengths) {
+    let hash_builder = DefaultHashBuilder::with_seed(0x5eed);
+    let entries = (CAPACITY as f64 * load_factor) as u64;
+    let mut occupied = vec![false; CAPACITY];
+
+    let probe = |hashed_key: u64, i: usize| {
+        let idx = Doubling.bucket_index(hashed_key, CAPACITY);
+        idx.wrapping_add(probe_strategy.offset(hashed_key, i, CAPACITY)) & (CAPACITY - 1)
+    };
+    // counts the buckets probed up to and including the first empty one
+    let probes_to_empty = |occupied: &[bool], hashed_key: u64| {
+        (0..CAPACITY)
+            .position(|i| !occupied[probe(hashed_key, i)])
+            .expect("the buckets are never full")
+            + 1
+    };
+
+    // a lookup of a stored key probes the same buckets as its insertion did
+    let mut hits = ProbeLengths::default();
+    for key in 0..entries {
+        let hashed_key = hash_builder.hash_one(key);
+        let probes = probes_to_empty(&occupied, hashed_key);
+        occupied[probe(hashed_key, probes - 1)] = true;
+        hits.record(probes);
+    }
+
+    let mut misses = ProbeLengths::default();
+    for key in entries..2 * entries {
+        misses.record(probes_to_empty(&occupied, hash_builder.hash_one(key)));
+    }
+    (hits, misses)
+}
+
+fn fill<P: ProbeStrategy>(probe_strategy: P) -> HashTable<u64, u64, DefaultHashBuilder, Doubling, P> {
+    let mut hash_table = HashTable::with_parts(0, DefaultHashBuilder::with_seed(0x5eed), Doubling, probe_strategy);
+    hash_table.set_max_probe(MAX_PROBE);
+    for i in 0..ENTRIES {
+        hash_table.insert(i, i);
+    }
+    hash_table
+}
+
+fn bench_probe_lengths<P: ProbeStrategy>(name: &str, probe_strategy: P) {
+    for load_factor in LOAD_FACTORS {
+        let (hits, misses) = probe_lengths(&probe_strategy, load_factor);
+        println!(
+            "{:<14} load factor: {:.2}  hit mean: {:>6.3} max: {:>4}  miss mean: {:>7.3} max: {:>5}",
+            name,
+            load_factor,
+            hits.mean(),
+            hits.max,
+            misses.mean(),
+            misses.max,
+        );
+    }
+}
+
+fn bench_timings<P: ProbeStrategy + Copy>(name: &str, probe_strategy: P) {
+    let insert = measure(|| {
+        black_box(fill(probe_strategy));
+    });
+    let hash_table = fill(probe_strategy);
+    let get = measure(|| {
+        for i in 0..ENTRIES {
+            black_box(hash_table.get(&black_box(i)));
+        }
+    });
+    println!("{:<14} insert: {:>10.3?}  get: {:>10.3?}", name, insert, get);
+}
+
+fn main() {
+    bench_probe_lengths("linear", Linear);
+    bench_probe_lengths("quadratic", Quadratic);
+    bench_probe_lengths("double hashing", DoubleHashing);
+
+    bench_timings("linear", Linear);
+    bench_timings("quadratic", Quadratic);
+    bench_timings("double hashing", DoubleHashing);
+}
@@ -1,180 +1,1230 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+//! Hash table resolving collisions by separate chaining.
+
+use std::array;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Index;
+use std::slice;
+use std::vec;
+use crate::hasher::{fibonacci_index, DefaultHashBuilder};
+use crate::map::Map;
 
 #[derive(Clone, Debug)]
-struct Bucket {
+struct Bucket<K, V> {
+    key: K,
     hashed_key: u64,
-    value: i32,
+    value: V,
 }
 
-type BucketChain = Vec<Bucket>;
+type BucketChain<K, V> = Vec<Bucket<K, V>>;
 
-type BucketChains = Vec<Option<BucketChain>>;
+type BucketChains<K, V> = Vec<Option<BucketChain<K, V>>>;
 
-#[derive(Debug)]
-struct HashTable {
-    chains: BucketChains,
+/// Hash table chaining colliding entries in per-bucket vectors.
+///
+/// The number of chains doubles when the load factor exceeds the maximum,
+/// and halves when it drops below a quarter of it,
+/// but never below the number of chains the table was created with.
+/// Keys are hashed by hashers built by the [`BuildHasher`] `S`.
+#[derive(Clone)]
+pub struct HashTable<K, V, S = DefaultHashBuilder> {
+    chains: BucketChains<K, V>,
+    len: usize,
+    // the number of chains the table never shrinks below
+    min_capacity: usize,
+    hash_builder: S,
+    max_load_factor: f64,
 }
 
-impl HashTable {
+impl<K, V> HashTable<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty table with the initial number of buckets.
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table with enough chains to hold `capacity` entries
+    /// without exceeding the maximum load factor.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table whose hashes, and therefore layout, are the same
+    /// for every table created with the same seed by a program built with the same toolchain.
+    ///
+    /// Tables created by [`HashTable::new`] draw random keys instead,
+    /// which keeps crafted keys from colliding on purpose.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_hasher(DefaultHashBuilder::with_seed(seed))
+    }
+}
+
+impl<K, V, S> HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
     const INITIAL_SIZE: usize = 16;
+    const DEFAULT_MAX_LOAD_FACTOR: f64 = 1.0;
 
-    pub fn new() -> Self {
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`,
+    /// with enough chains to hold `capacity` entries without exceeding the maximum load factor.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut min_capacity = Self::INITIAL_SIZE;
+        while capacity as f64 > Self::DEFAULT_MAX_LOAD_FACTOR * min_capacity as f64 {
+            min_capacity *= 2;
+        }
+        HashTable{
+            chains: Self::make_empty_chains(min_capacity),
+            len: 0,
+            min_capacity,
+            hash_builder,
+            max_load_factor: Self::DEFAULT_MAX_LOAD_FACTOR,
+        }
+    }
+
+    /// Returns a reference to the builder of the hashers of the table.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    fn make_empty_chains(len: usize) -> BucketChains<K, V> {
         let mut chains = Vec::new();
-        chains.resize(Self::INITIAL_SIZE, None);
-        HashTable{chains}
+        chains.resize_with(len, || None);
+        chains
     }
 
-    fn len(&self) -> usize {
+    /// Returns the load factor above which the chains are doubled.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Sets the load factor above which the chains are doubled,
+    /// resizing the table right away if it no longer fits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_load_factor` is not a positive number.
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) {
+        assert!(max_load_factor > 0.0, "max load factor must be positive");
+        self.max_load_factor = max_load_factor;
+        self.resize_to_fit(self.len);
+    }
+
+    /// Returns the number of chains.
+    pub fn capacity(&self) -> usize {
         self.chains.len()
     }
 
-    fn compute_hash(&self, key: &str) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish()
+    /// Returns the ratio of stored entries to chains.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    // doubles the chains while len entries would exceed the maximum load factor,
+    // and halves them while they would stay below a quarter of it
+    fn resize_to_fit(&mut self, len: usize) {
+        let len = len as f64;
+        let mut next_len = self.capacity();
+        while len > self.max_load_factor * next_len as f64 {
+            next_len *= 2;
+        }
+        while next_len > self.min_capacity && len < self.max_load_factor * next_len as f64 / 4.0 {
+            next_len /= 2;
+        }
+        if next_len != self.capacity() {
+            self.resize(next_len);
+        }
+    }
+
+    // redistributes every bucket into next_len chains by its cached hash,
+    // without hashing any key again
+    fn resize(&mut self, next_len: usize) {
+        let mut new_chains = Self::make_empty_chains(next_len);
+        for bucket in mem::take(&mut self.chains).into_iter().flatten().flatten() {
+            let idx = self.compute_bucket_index(bucket.hashed_key, next_len);
+            new_chains[idx].get_or_insert_with(Vec::new).push(bucket);
+        }
+        self.chains = new_chains;
+    }
+
+    fn compute_hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
     }
 
     fn compute_bucket_index(&self, hashed_key: u64, len: usize) -> usize {
-        (hashed_key % (len as u64)) as usize
-    }
-
-    pub fn upsert(&mut self, key: String, value: i32) {
-        let hashed_key = self.compute_hash(key.as_str());
-        let idx = self.compute_bucket_index(hashed_key, self.len());
-
-        match &mut self.chains[idx] {
-            // insert value if the bucket is empty
-            None => {
-                let mut chain = Vec::new();
-                let bucket = Bucket {
-                    hashed_key: hashed_key,
-                    value: value,
-                };
-                chain.push(bucket);
-                self.chains[idx] = Some(chain);
-            },
-            Some(chain) => {
-                let bucket = Bucket {
-                    hashed_key: hashed_key,
-                    value: value,
-                };
-                // update value if the hashed key collides
-                for bucket in chain.iter_mut() {
-                    if bucket.hashed_key == hashed_key {
-                        bucket.value = value;
-                        return;
-                    }
-                }
-                // isnert value into the tail of chain
-                chain.push(bucket);
+        fibonacci_index(hashed_key, len)
+    }
+
+    /// Inserts the value, returning the value it replaced if the key was already stored.
+    ///
+    /// The stored key is kept when its value is replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            // update value if the same key is already chained
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
             }
         }
     }
 
-    pub fn get(&self, key: &str) -> Option<i32> {
-        let hashed_key = self.compute_hash(key);
-        let idx = self.compute_bucket_index(hashed_key, self.len());
+    /// Returns the entry of the key for in-place manipulation,
+    /// hashing the key and walking its chain only once.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hashed_key = self.compute_hash(&key);
+        let idx = self.compute_bucket_index(hashed_key, self.capacity());
+
+        let pos = self.chains[idx]
+            .as_ref()
+            .and_then(|chain| chain.iter().position(|bucket| bucket.hashed_key == hashed_key && bucket.key == key));
+        if let Some(pos) = pos {
+            return Entry::Occupied(OccupiedEntry { table: self, idx, pos });
+        }
+
+        // grow ahead so that the chain of the new entry stays put
+        self.resize_to_fit(self.len + 1);
+        let idx = self.compute_bucket_index(hashed_key, self.capacity());
+        Entry::Vacant(VacantEntry { table: self, key, hashed_key, idx })
+    }
+
+    /// Returns a reference to the value stored for the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (idx, pos) = self.find_position(key)?;
+        self.chains[idx].as_ref().map(|chain| &chain[pos].value)
+    }
 
-        if self.chains[idx].is_none() {
+    /// Returns a mutable reference to the value stored for the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (idx, pos) = self.find_position(key)?;
+        self.chains[idx].as_mut().map(|chain| &mut chain[pos].value)
+    }
+
+    /// Returns the stored key and a reference to its value.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (idx, pos) = self.find_position(key)?;
+        self.chains[idx].as_ref().map(|chain| (&chain[pos].key, &chain[pos].value))
+    }
+
+    /// Returns mutable references to the values stored for `N` distinct keys at once.
+    ///
+    /// Returns `None` if any of the keys is not stored or the same key is given twice.
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut positions = [(0, 0); N];
+        for (position, key) in positions.iter_mut().zip(keys) {
+            *position = self.find_position(key)?;
+        }
+
+        // visit the positions in order so that every bucket is borrowed once
+        let mut order: [usize; N] = array::from_fn(|i| i);
+        order.sort_unstable_by_key(|&i| positions[i]);
+        if order.windows(2).any(|pair| positions[pair[0]] == positions[pair[1]]) {
             return None;
         }
 
-        let chain = self.chains[idx].as_ref().unwrap();
-        for bucket in chain {
-            if bucket.hashed_key == hashed_key {
-                return Some(bucket.value);
+        let mut values: [Option<&mut V>; N] = array::from_fn(|_| None);
+        let mut chains = self.chains.iter_mut();
+        let mut next_idx = 0;
+        let mut i = 0;
+        while i < N {
+            let idx = positions[order[i]].0;
+            let mut buckets = chains.nth(idx - next_idx)?.as_mut()?.iter_mut();
+            next_idx = idx + 1;
+
+            let mut next_pos = 0;
+            while i < N && positions[order[i]].0 == idx {
+                let pos = positions[order[i]].1;
+                values[order[i]] = Some(&mut buckets.nth(pos - next_pos)?.value);
+                next_pos = pos + 1;
+                i += 1;
             }
         }
+        Some(values.map(|value| value.expect("every position is visited")))
+    }
 
-        None
+    /// Applies `f` to the value stored for the key in place,
+    /// returning `true` if the key was stored.
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V),
+    {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            },
+            None => false,
+        }
     }
 
-    pub fn delete(&mut self, key: &str) {
+    /// Returns `true` if an entry is stored for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_position(key).is_some()
+    }
+
+    // returns the chain index and the position in the chain of the key
+    fn find_position<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         let hashed_key = self.compute_hash(key);
-        let idx = self.compute_bucket_index(hashed_key, self.len());
+        let idx = self.compute_bucket_index(hashed_key, self.capacity());
+
+        let chain = self.chains[idx].as_ref()?;
+        let pos = chain.iter().position(|bucket| bucket.hashed_key == hashed_key && bucket.key.borrow() == key)?;
+        Some((idx, pos))
+    }
+
+    /// Returns the number of entries stored in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the table stores no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry, keeping the allocated chains.
+    pub fn clear(&mut self) {
+        self.chains.fill_with(|| None);
+        self.len = 0;
+    }
+
+    /// Removes every entry, returning them through an iterator.
+    ///
+    /// The table keeps its number of chains.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        let empty_chains = Self::make_empty_chains(self.capacity());
+        let chains = mem::replace(&mut self.chains, empty_chains);
+        let remaining = mem::take(&mut self.len);
+        Drain {
+            inner: IntoIter { chains: chains.into_iter(), chain: Vec::new().into_iter(), remaining },
+            marker: PhantomData,
+        }
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for chain in self.chains.iter_mut().flatten() {
+            let before = chain.len();
+            chain.retain_mut(|bucket| f(&bucket.key, &mut bucket.value));
+            self.len -= before - chain.len();
+        }
+        self.resize_to_fit(self.len);
+    }
+
+    /// Returns an iterator removing and yielding the entries for which `pred` returns `true`.
+    ///
+    /// Entries are only removed as the iterator advances,
+    /// and the table shrinks if needed once the iterator is dropped.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, S, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf { table: self, idx: 0, pos: 0, pred }
+    }
+
+    /// Returns an iterator over the stored entries, chain by chain.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored entries, chain by chain,
+    /// with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the stored values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Returns an iterator over mutable references to the stored values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { inner: self.iter_mut() }
+    }
+
+    /// Consumes the table into an iterator over the stored keys.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys { inner: self.into_iter() }
+    }
+
+    /// Consumes the table into an iterator over the stored values.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues { inner: self.into_iter() }
+    }
+
+    /// Removes the entry stored for the key, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the entry stored for the key, returning the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (idx, pos) = self.find_position(key)?;
+        let bucket = self.remove_at(idx, pos);
+        Some((bucket.key, bucket.value))
+    }
+
+    fn remove_at(&mut self, idx: usize, pos: usize) -> Bucket<K, V> {
+        let chain = self.chains[idx].as_mut().expect("only chained buckets are removed");
+        let bucket = chain.remove(pos);
+        self.len -= 1;
+        self.resize_to_fit(self.len);
+        bucket
+    }
+}
+
+impl<K, V, S> Default for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for HashTable<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, S> PartialEq for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S> Eq for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    V: Eq,
+{
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hash_table = Self::default();
+        hash_table.extend(iter);
+        hash_table
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq + Copy,
+    S: BuildHasher,
+    V: Copy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, S, Q> Index<&Q> for HashTable<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    S: BuildHasher,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value stored for the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not stored.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key is not stored in the table")
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashTable<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        Iter { chains: self.chains.iter(), chain: [].iter(), remaining: self.len }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashTable<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        IterMut { chains: self.chains.iter_mut(), chain: [].iter_mut(), remaining: self.len }
+    }
+}
+
+impl<K, V, S> Map<K, V> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Iter<'a> = Iter<'a, K, V> where Self: 'a, K: 'a, V: 'a;
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key)
+    }
 
-        if self.chains[idx].is_none() {
-            return;
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove(key)
+    }
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn iter(&self) -> Iter<'_, K, V> {
+        self.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for HashTable<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consumes the table into an iterator over the stored entries, chain by chain.
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { chains: self.chains.into_iter(), chain: Vec::new().into_iter(), remaining: self.len }
+    }
+}
+
+/// Iterator over the entries of a [`HashTable`], created by [`HashTable::iter`].
+pub struct Iter<'a, K, V> {
+    chains: slice::Iter<'a, Option<BucketChain<K, V>>>,
+    chain: slice::Iter<'a, Bucket<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(bucket) = self.chain.next() {
+                self.remaining -= 1;
+                return Some((&bucket.key, &bucket.value));
+            }
+            // move on to the next chain when the current one is exhausted
+            self.chain = self.chains.next()?.as_deref().unwrap_or_default().iter();
         }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
 
-        let chain = self.chains[idx].as_mut().unwrap();
-        let mut delete_idx = None;
-        
-        for (i, bucket) in chain.iter().enumerate() {
-            if bucket.hashed_key == hashed_key {
-                delete_idx = Some(i);
-                break;
+/// Iterator over the entries of a [`HashTable`] with mutable references to the values,
+/// created by [`HashTable::iter_mut`].
+pub struct IterMut<'a, K, V> {
+    chains: slice::IterMut<'a, Option<BucketChain<K, V>>>,
+    chain: slice::IterMut<'a, Bucket<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(bucket) = self.chain.next() {
+                self.remaining -= 1;
+                return Some((&bucket.key, &mut bucket.value));
             }
+            // move on to the next chain when the current one is exhausted
+            self.chain = self.chains.next()?.as_deref_mut().unwrap_or_default().iter_mut();
         }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owning iterator over the entries of a [`HashTable`], created by [`HashTable::into_iter`].
+pub struct IntoIter<K, V> {
+    chains: vec::IntoIter<Option<BucketChain<K, V>>>,
+    chain: vec::IntoIter<Bucket<K, V>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
 
-        if delete_idx.is_some() {
-            chain.remove(delete_idx.unwrap());
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(bucket) = self.chain.next() {
+                self.remaining -= 1;
+                return Some((bucket.key, bucket.value));
+            }
+            // move on to the next chain when the current one is exhausted
+            self.chain = self.chains.next()?.unwrap_or_default().into_iter();
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+/// Draining iterator over the entries of a [`HashTable`], created by [`HashTable::drain`].
+pub struct Drain<'a, K, V> {
+    inner: IntoIter<K, V>,
+    marker: PhantomData<&'a mut HashTable<K, V>>,
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+/// Iterator removing the entries of a [`HashTable`] that match a predicate,
+/// created by [`HashTable::extract_if`].
+pub struct ExtractIf<'a, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    table: &'a mut HashTable<K, V, S>,
+    idx: usize,
+    pos: usize,
+    pred: F,
+}
+
+impl<K, V, S, F> Iterator for ExtractIf<'_, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.table.capacity() {
+            let Some(chain) = self.table.chains[self.idx].as_mut() else {
+                self.idx += 1;
+                continue;
+            };
+            while self.pos < chain.len() {
+                let bucket = &mut chain[self.pos];
+                if (self.pred)(&bucket.key, &mut bucket.value) {
+                    // the last bucket moves into pos, which is visited next
+                    let bucket = chain.swap_remove(self.pos);
+                    self.table.len -= 1;
+                    return Some((bucket.key, bucket.value));
+                }
+                self.pos += 1;
+            }
+            // move on to the next chain when the current one is exhausted
+            self.idx += 1;
+            self.pos = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.table.len))
+    }
+}
+
+impl<K, V, S, F> Drop for ExtractIf<'_, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    fn drop(&mut self) {
+        // the chains stay put while iterating, so shrink only now
+        self.table.resize_to_fit(self.table.len);
+    }
+}
+
+/// Iterator over the keys of a [`HashTable`], created by [`HashTable::keys`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// Iterator over the values of a [`HashTable`], created by [`HashTable::values`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// Iterator over mutable references to the values of a [`HashTable`],
+/// created by [`HashTable::values_mut`].
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+/// Owning iterator over the keys of a [`HashTable`], created by [`HashTable::into_keys`].
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+/// Owning iterator over the values of a [`HashTable`], created by [`HashTable::into_values`].
+pub struct IntoValues<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+/// View into a single entry of a [`HashTable`], created by [`HashTable::entry`].
+pub enum Entry<'a, K, V, S = DefaultHashBuilder> {
+    /// The key is stored in the table.
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    /// The key is not stored in the table.
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// View into an entry whose key is stored in a [`HashTable`].
+pub struct OccupiedEntry<'a, K, V, S = DefaultHashBuilder> {
+    table: &'a mut HashTable<K, V, S>,
+    idx: usize,
+    pos: usize,
+}
+
+/// View into an entry whose key is not stored in a [`HashTable`] yet.
+pub struct VacantEntry<'a, K, V, S = DefaultHashBuilder> {
+    table: &'a mut HashTable<K, V, S>,
+    key: K,
+    hashed_key: u64,
+    idx: usize,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the stored value, inserting `default` if the key is not stored.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the stored value, inserting the result of `default` if the key is not stored.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Returns the stored value, inserting the result of `default` for the key
+    /// if the key is not stored.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns the stored value, inserting `V::default()` if the key is not stored.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Applies `f` to the stored value if the key is stored.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Stores the value whether the key is stored or not.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            },
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn bucket(&self) -> &Bucket<K, V> {
+        &self.table.chains[self.idx].as_ref().expect("occupied entry points at a chained bucket")[self.pos]
+    }
+
+    fn bucket_mut(&mut self) -> &mut Bucket<K, V> {
+        &mut self.table.chains[self.idx].as_mut().expect("occupied entry points at a chained bucket")[self.pos]
+    }
+
+    /// Returns the stored key.
+    pub fn key(&self) -> &K {
+        &self.bucket().key
+    }
+
+    /// Returns a reference to the stored value.
+    pub fn get(&self) -> &V {
+        &self.bucket().value
+    }
+
+    /// Returns a mutable reference to the stored value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.bucket_mut().value
+    }
+
+    /// Converts the entry into a mutable reference to the stored value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.chains[self.idx].as_mut().expect("occupied entry points at a chained bucket")[self.pos].value
+    }
+
+    /// Replaces the stored value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry, returning the stored key and its value.
+    pub fn remove_entry(self) -> (K, V) {
+        let bucket = self.table.remove_at(self.idx, self.pos);
+        (bucket.key, bucket.value)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the key that would be stored.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back the key without storing anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Stores the value for the key, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    /// Stores the value for the key, returning the now occupied entry.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        let table = self.table;
+        // insert value into the tail of chain
+        let chain = table.chains[self.idx].get_or_insert_with(Vec::new);
+        chain.push(Bucket {
+            key: self.key,
+            hashed_key: self.hashed_key,
+            value,
+        });
+        let pos = chain.len() - 1;
+        table.len += 1;
+        OccupiedEntry { table, idx: self.idx, pos }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{shared_tests, CollidingKey, CountingHashBuilder, IdentityHasher};
+    use std::hash::BuildHasherDefault;
+
+    shared_tests!();
 
     #[test]
-    fn it_works() {
+    fn colliding_hashes_keep_keys_apart() {
         // Setup
         let mut hash_table = HashTable::new();
-        
+
+        // Exercise: insert keys whose hashes are identical
+        for i in 0..10 {
+            hash_table.insert(CollidingKey(i), i);
+        }
+
+        // Verify: each key keeps its own value
+        for i in 0..10 {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+        assert!(hash_table.get(&CollidingKey(10)).is_none());
+
+        // Exercise: delete one key
+        hash_table.remove(&CollidingKey(3));
+
+        // Verify: only the deleted key is gone
+        assert!(hash_table.get(&CollidingKey(3)).is_none());
+        for i in (0..10).filter(|&i| i != 3) {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+
+        // Exercise & Verify: disjoint keys within one chain are borrowed together
+        let [a, b] = hash_table.get_many_mut([&CollidingKey(9), &CollidingKey(0)]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(Some(&9), hash_table.get(&CollidingKey(0)));
+        assert_eq!(Some(&0), hash_table.get(&CollidingKey(9)));
+    }
+
+    #[test]
+    fn len_counts_entries() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        assert!(hash_table.is_empty());
+        assert_eq!(HashTable::<String, i32>::INITIAL_SIZE, hash_table.capacity());
+
         // Exercise: insert
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let value = i;
-            hash_table.upsert(key, value)
+        for i in 0..100 {
+            hash_table.insert(format!("key{}", i), i);
         }
 
-        // Verify: get (found)
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let expected_value = i;
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+        // Verify: every insert is counted
+        assert_eq!(100, hash_table.len());
+        assert_eq!(100.0 / hash_table.capacity() as f64, hash_table.load_factor());
+
+        // Exercise: overwrite
+        for i in 0..100 {
+            hash_table.insert(format!("key{}", i), i * 2);
         }
 
-        // Exercise: update
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let value = i * 2;
-            hash_table.upsert(key, value)
+        // Verify: overwrites are not counted
+        assert_eq!(100, hash_table.len());
+
+        // Exercise: delete, twice for the same key and once for a missing key
+        for i in 0..40 {
+            hash_table.remove(format!("key{}", i).as_str());
+            hash_table.remove(format!("key{}", i).as_str());
+        }
+        hash_table.remove("key100");
+
+        // Verify: deleted entries are not counted
+        assert_eq!(60, hash_table.len());
+        assert_eq!(60, hash_table.iter().count());
+
+        // Exercise: clear
+        hash_table.clear();
+
+        // Verify: nothing is counted
+        assert!(hash_table.is_empty());
+        assert_eq!(0.0, hash_table.load_factor());
+    }
+
+    #[test]
+    fn resizes_with_load_factor() {
+        // Setup
+        let mut hash_table = HashTable::new();
+
+        // Exercise: insert far more keys than the initial chains
+        for i in 0..10000 {
+            hash_table.insert(i, i);
         }
 
-        // Verify: update
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let expected_value = i * 2;
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+        // Verify: the chains grew and every key is still reachable
+        assert!(hash_table.load_factor() <= hash_table.max_load_factor());
+        assert!(hash_table.capacity() >= 10000);
+        for i in 0..10000 {
+            assert_eq!(Some(&i), hash_table.get(&i));
         }
 
-        // Verify: get (not found)
-        {
-            let actual = hash_table.get("key100");
-            assert!(actual.is_none());
+        // Exercise: delete most of the keys
+        for i in 100..10000 {
+            hash_table.remove(&i);
         }
 
-        // Exercise: delete and get (not found)
-        for i in 1..50 {
-            let key = format!("key{}", i);
-            hash_table.delete(key.as_str());
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_none());
+        // Verify: the chains shrank and the remaining keys are reachable
+        assert!(hash_table.capacity() < 10000);
+        assert!(hash_table.load_factor() >= hash_table.max_load_factor() / 4.0);
+        for i in 0..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
         }
 
-        // Exercise: insert and get (found)
-        for expected_value in 1..50 {
-            let key = format!("key{}", expected_value);
-            hash_table.upsert(key.clone(), expected_value);
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+        // Exercise: lower the maximum load factor
+        hash_table.set_max_load_factor(0.25);
+
+        // Verify: the chains grew to fit the new maximum
+        assert!(hash_table.capacity() >= 400);
+        assert!(hash_table.load_factor() <= 0.25);
+        for i in 0..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
         }
     }
+
+    #[test]
+    fn custom_hasher() {
+        // Setup
+        let mut hash_table = HashTable::with_capacity_and_hasher(12, BuildHasherDefault::<IdentityHasher>::default());
+        let capacity = hash_table.capacity();
+
+        // Exercise
+        for key in (0..12u64).rev() {
+            hash_table.insert(key, key * 10);
+        }
+
+        // Verify: the table had room for every entry, and each key landed in its own bucket
+        assert_eq!(capacity, hash_table.capacity());
+        assert_eq!(5, hash_table.hasher().hash_one(5u64));
+        let keys: Vec<u64> = hash_table.keys().copied().collect();
+        let mut expected: Vec<u64> = (0..12).collect();
+        expected.sort_by_key(|&key| fibonacci_index(key, capacity));
+        assert_eq!(expected, keys);
+        assert_eq!(Some(&50), hash_table.get(&5));
+    }
+
+    #[test]
+    fn with_capacity_never_shrinks_below_it() {
+        // Setup
+        let mut hash_table = HashTable::with_capacity(1000);
+        let capacity = hash_table.capacity();
+        assert!(1000.0 <= hash_table.max_load_factor() * capacity as f64);
+
+        // Exercise
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+        hash_table.retain(|_, _| false);
+
+        // Verify
+        assert_eq!(capacity, hash_table.capacity());
+    }
+
+    #[test]
+    fn resizing_reuses_cached_hashes() {
+        // Setup
+        let hash_builder = CountingHashBuilder::default();
+        let mut hash_table = HashTable::with_hasher(hash_builder.clone());
+
+        // Exercise: grow through several resizes
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: every key was hashed once, on insertion
+        assert_eq!(1024, hash_table.capacity());
+        assert_eq!(1000, hash_builder.hashes());
+
+        // Exercise: shrink by removal, then resize in every other way
+        for i in 0..500 {
+            hash_table.remove(&i);
+        }
+        hash_table.set_max_load_factor(0.25);
+        hash_table.retain(|key, _| key % 2 == 0);
+        hash_table.extract_if(|key, _| key % 3 == 0).for_each(drop);
+        let cloned = hash_table.clone();
+
+        // Verify: only the removals hashed their keys
+        assert_eq!(1500, hash_builder.hashes());
+        assert_eq!(167, cloned.len());
+    }
 }
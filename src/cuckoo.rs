@@ -0,0 +1,1422 @@
+//! Hash table resolving collisions by cuckoo hashing.
+
+use std::array;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Index;
+use std::slice;
+use std::vec;
+use crate::hasher::{fibonacci_index, mix, DefaultHashBuilder};
+use crate::map::Map;
+
+#[derive(Clone, Debug)]
+struct Bucket<K, V> {
+    key: K,
+    hashed_key: u64,
+    value: V,
+}
+
+type Buckets<K, V> = Vec<Option<Bucket<K, V>>>;
+
+// iterates the stash followed by the overflow stash
+type Stashes<I> = iter::Chain<I, I>;
+
+// where an entry is stored: one of its two buckets, the stash, or the overflow stash
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Location {
+    Bucket(usize),
+    Stash(usize),
+    Overflow(usize),
+}
+
+/// Hash table storing every entry in one of two buckets chosen by its hash.
+///
+/// A lookup checks those two buckets and a stash of at most a few entries,
+/// so it takes constant time even in the worst case, unless keys share whole hashes.
+/// An insertion into two occupied buckets evicts one of their entries into its other bucket,
+/// and so on for a bounded number of evictions; the entry left over then goes to the stash.
+/// When the stash is full, the table rehashes with new seeds for the two buckets,
+/// and grows when new seeds keep failing or when the load factor exceeds its maximum.
+/// Both buckets are derived from the hash of the key, so no seeds separate keys sharing
+/// a whole hash; those that find both of their buckets taken by such keys are kept
+/// in an overflow stash, which lookups check last.
+/// Keys are hashed by hashers built by the [`BuildHasher`] `S`.
+#[derive(Clone)]
+pub struct HashTable<K, V, S = DefaultHashBuilder> {
+    buckets: Buckets<K, V>,
+    stash: Vec<Bucket<K, V>>,
+    // entries whose buckets both hold entries sharing their whole hash
+    overflow: Vec<Bucket<K, V>>,
+    len: usize,
+    // mixed into the cached hash to pick the two buckets of an entry
+    seeds: [u64; 2],
+    hash_builder: S,
+    max_load_factor: f64,
+}
+
+impl<K, V> HashTable<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty table with the initial number of buckets.
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table with enough buckets to hold `capacity` entries
+    /// without exceeding the maximum load factor.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table whose hashes, and therefore layout, are the same
+    /// for every table created with the same seed by a program built with the same toolchain.
+    ///
+    /// Tables created by [`HashTable::new`] draw random keys instead,
+    /// which keeps crafted keys from colliding on purpose.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_hasher(DefaultHashBuilder::with_seed(seed))
+    }
+}
+
+impl<K, V, S> HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    const INITIAL_SIZE: usize = 16;
+    // two buckets per entry fill up reliably only up to about half of them
+    const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.5;
+    const MAX_EVICTIONS: usize = 32;
+    const MAX_STASH: usize = 4;
+    // the table grows after failing to rehash with this many seeds in a row
+    const MAX_RESEEDS: usize = 3;
+    // entries of distinct hashes all find a place long before the table grows eightfold
+    const MAX_FAILED_REHASHES: usize = 8 * Self::MAX_RESEEDS;
+    const INITIAL_SEEDS: [u64; 2] = [0x243f_6a88_85a3_08d3, 0x1319_8a2e_0370_7344];
+
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`,
+    /// with enough buckets to hold `capacity` entries without exceeding the maximum load factor.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut len = Self::INITIAL_SIZE;
+        while capacity as f64 > Self::DEFAULT_MAX_LOAD_FACTOR * len as f64 {
+            len *= 2;
+        }
+        HashTable {
+            buckets: Self::make_empty_buckets(len),
+            stash: Vec::new(),
+            overflow: Vec::new(),
+            len: 0,
+            seeds: Self::INITIAL_SEEDS,
+            hash_builder,
+            max_load_factor: Self::DEFAULT_MAX_LOAD_FACTOR,
+        }
+    }
+
+    /// Returns a reference to the builder of the hashers of the table.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the load factor above which the table grows.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Sets the load factor above which the table grows,
+    /// growing the table right away if it no longer fits.
+    ///
+    /// Maximums above one half make eviction cycles, and so rehashes, much more frequent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_load_factor` is not in `(0, 1]`.
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) {
+        assert!(max_load_factor > 0.0 && max_load_factor <= 1.0, "max load factor must be in (0, 1]");
+        self.max_load_factor = max_load_factor;
+
+        let mut next_len = self.capacity();
+        while self.len as f64 > self.max_load_factor * next_len as f64 {
+            next_len *= 2;
+        }
+        if next_len != self.capacity() {
+            self.rehash_to(next_len);
+        }
+    }
+
+    fn make_empty_buckets(len: usize) -> Buckets<K, V> {
+        let mut buckets = Vec::new();
+        buckets.resize_with(len, || None);
+        buckets
+    }
+
+    /// Returns the number of buckets, not counting the stash.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns the ratio of stored entries to buckets.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    fn compute_hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    // returns the two buckets of the hash among len buckets,
+    // scrambled with each seed so that the two are unrelated
+    fn compute_bucket_indices(&self, hashed_key: u64, len: usize) -> [usize; 2] {
+        self.seeds.map(|seed| fibonacci_index(mix(hashed_key ^ seed), len))
+    }
+
+    // derives new seeds from the current ones, so that seeded tables stay reproducible
+    fn reseed(&mut self) {
+        self.seeds = self.seeds.map(|seed| mix(seed.wrapping_add(GOLDEN_GAMMA)));
+    }
+
+    /// Inserts the value, returning the value it replaced if the key was already stored.
+    ///
+    /// The stored key is kept when its value is replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            // replace value when the key is already stored
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Returns the entry of the key for in-place manipulation,
+    /// hashing the key and looking it up only once.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hashed_key = self.compute_hash(&key);
+        if let Some(location) = self.locate(hashed_key, |stored| *stored == key) {
+            return Entry::Occupied(OccupiedEntry { table: self, location });
+        }
+
+        // grow ahead when the new entry would crowd the table
+        if (self.len + 1) as f64 > self.max_load_factor * self.capacity() as f64 {
+            self.rehash_to(self.capacity() * 2);
+        }
+        Entry::Vacant(VacantEntry { table: self, key, hashed_key })
+    }
+
+    // stores the entry of a key known to be absent, returning where it ended up
+    fn insert_absent(&mut self, mut bucket: Bucket<K, V>) -> Location {
+        let mut failures = 0;
+        loop {
+            let (idx, homeless) = match self.evict_into(bucket) {
+                (Some(idx), None) => return Location::Bucket(idx),
+                (idx, Some(homeless)) => (idx, homeless),
+                (None, None) => unreachable!("an entry is either placed or left over"),
+            };
+            match self.stash_homeless(homeless) {
+                Ok(location) => return idx.map_or(location, Location::Bucket),
+                // otherwise rehash with new seeds, setting the inserted entry aside to place it last
+                Err(homeless) => {
+                    bucket = match idx {
+                        Some(idx) => {
+                            // the stash goes over its size only until the rehash places its entries
+                            self.stash.push(homeless);
+                            self.buckets[idx].take().expect("the inserted entry is in its bucket")
+                        },
+                        None => homeless,
+                    };
+                    let next_len = self.reseed_after_failure(&mut failures, self.capacity());
+                    self.rehash_to(next_len);
+                },
+            }
+        }
+    }
+
+    // keeps the entry the evictions left over in the stash while it has room,
+    // or in the overflow stash if no seeds can separate it from the entries in its buckets;
+    // hands the entry back when neither takes it
+    fn stash_homeless(&mut self, homeless: Bucket<K, V>) -> Result<Location, Bucket<K, V>> {
+        if self.stash.len() < Self::MAX_STASH {
+            self.stash.push(homeless);
+            return Ok(Location::Stash(self.stash.len() - 1));
+        }
+        let inseparable = self
+            .compute_bucket_indices(homeless.hashed_key, self.capacity())
+            .into_iter()
+            .all(|idx| self.buckets[idx].as_ref().is_some_and(|resident| resident.hashed_key == homeless.hashed_key));
+        if inseparable {
+            self.overflow.push(homeless);
+            return Ok(Location::Overflow(self.overflow.len() - 1));
+        }
+        Err(homeless)
+    }
+
+    // places the entry into one of its two buckets, evicting the entry there into its
+    // other bucket and so on for at most MAX_EVICTIONS entries;
+    // returns the bucket the given entry ended up in,
+    // and the entry left without a bucket once the evictions run out
+    fn evict_into(&mut self, bucket: Bucket<K, V>) -> (Option<usize>, Option<Bucket<K, V>>) {
+        let len = self.capacity();
+        let [first, second] = self.compute_bucket_indices(bucket.hashed_key, len);
+        let mut idx = if self.buckets[first].is_some() && self.buckets[second].is_none() { second } else { first };
+
+        let mut carried = bucket;
+        // the given entry is carried until placed, and again if a later eviction reaches it
+        let mut carrying_given = true;
+        let mut given_idx = None;
+        for _ in 0..Self::MAX_EVICTIONS {
+            match self.buckets[idx].replace(carried) {
+                None => {
+                    if carrying_given {
+                        given_idx = Some(idx);
+                    }
+                    return (given_idx, None);
+                },
+                Some(evicted) => {
+                    let evicted_given = given_idx == Some(idx);
+                    if carrying_given {
+                        given_idx = Some(idx);
+                    } else if evicted_given {
+                        given_idx = None;
+                    }
+                    carrying_given = evicted_given;
+
+                    // move the evicted entry to its other bucket
+                    let [first, second] = self.compute_bucket_indices(evicted.hashed_key, len);
+                    idx = if idx == first { second } else { first };
+                    carried = evicted;
+                }
+            }
+        }
+        (given_idx, Some(carried))
+    }
+
+    // moves every entry, stashed ones included, into next_len buckets by their cached hashes,
+    // reseeding after every failure and growing after repeated ones
+    fn rehash_to(&mut self, mut next_len: usize) {
+        let mut entries: Vec<Bucket<K, V>> = mem::take(&mut self.buckets).into_iter().flatten().collect();
+        entries.append(&mut self.stash);
+        entries.append(&mut self.overflow);
+
+        let mut failures = 0;
+        loop {
+            match self.make_rehashed_buckets(next_len, entries) {
+                Ok(()) => return,
+                Err(returned) => {
+                    entries = returned;
+                    next_len = self.reseed_after_failure(&mut failures, next_len);
+                }
+            }
+        }
+    }
+
+    // reseeds after failing to place some entry into len buckets,
+    // returning the number of buckets to retry with, which doubles every MAX_RESEEDS failures
+    fn reseed_after_failure(&mut self, failures: &mut usize, len: usize) -> usize {
+        *failures += 1;
+        assert!(*failures < Self::MAX_FAILED_REHASHES, "cuckoo hashing failed to separate distinct hashes");
+        self.reseed();
+        if failures.is_multiple_of(Self::MAX_RESEEDS) { len * 2 } else { len }
+    }
+
+    // moves entries into next_len empty buckets and the stashes,
+    // or hands every entry back when some of them cannot be placed
+    fn make_rehashed_buckets(&mut self, next_len: usize, entries: Vec<Bucket<K, V>>) -> Result<(), Vec<Bucket<K, V>>> {
+        self.buckets = Self::make_empty_buckets(next_len);
+        let mut entries = entries.into_iter();
+        while let Some(bucket) = entries.next() {
+            if let (_, Some(homeless)) = self.evict_into(bucket) {
+                if let Err(homeless) = self.stash_homeless(homeless) {
+                    let mut returned: Vec<Bucket<K, V>> = mem::take(&mut self.buckets).into_iter().flatten().collect();
+                    returned.append(&mut self.stash);
+                    returned.append(&mut self.overflow);
+                    returned.push(homeless);
+                    returned.extend(entries);
+                    return Err(returned);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // moves stashed entries, overflowed ones included,
+    // into any of their buckets that has become free
+    fn unstash(&mut self) {
+        let stash = mem::take(&mut self.stash);
+        self.stash = self.place_into_free_buckets(stash);
+        let overflow = mem::take(&mut self.overflow);
+        self.overflow = self.place_into_free_buckets(overflow);
+    }
+
+    // moves the entries into their free buckets, returning those that found none
+    fn place_into_free_buckets(&mut self, entries: Vec<Bucket<K, V>>) -> Vec<Bucket<K, V>> {
+        let len = self.capacity();
+        entries
+            .into_iter()
+            .filter_map(|bucket| {
+                let indices = self.compute_bucket_indices(bucket.hashed_key, len);
+                match indices.into_iter().find(|&idx| self.buckets[idx].is_none()) {
+                    Some(idx) => {
+                        self.buckets[idx] = Some(bucket);
+                        None
+                    },
+                    None => Some(bucket),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a reference to the value stored for the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let location = self.find_location(key)?;
+        Some(&self.bucket(location).value)
+    }
+
+    /// Returns a mutable reference to the value stored for the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let location = self.find_location(key)?;
+        Some(&mut self.bucket_mut(location).value)
+    }
+
+    /// Returns the stored key and a reference to its value.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.bucket(self.find_location(key)?);
+        Some((&bucket.key, &bucket.value))
+    }
+
+    /// Returns mutable references to the values stored for `N` distinct keys at once.
+    ///
+    /// Returns `None` if any of the keys is not stored or the same key is given twice.
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        // number the buckets first, then the stashed entries, then the overflowed ones
+        let capacity = self.capacity();
+        let stashed = self.stash.len();
+        let mut positions = [0; N];
+        for (position, key) in positions.iter_mut().zip(keys) {
+            *position = match self.find_location(key)? {
+                Location::Bucket(idx) => idx,
+                Location::Stash(pos) => capacity + pos,
+                Location::Overflow(pos) => capacity + stashed + pos,
+            };
+        }
+
+        // visit the positions in order so that every bucket is borrowed once
+        let mut order: [usize; N] = array::from_fn(|i| i);
+        order.sort_unstable_by_key(|&i| positions[i]);
+        if order.windows(2).any(|pair| positions[pair[0]] == positions[pair[1]]) {
+            return None;
+        }
+
+        let mut values: [Option<&mut V>; N] = array::from_fn(|_| None);
+        let mut slots = self
+            .buckets
+            .iter_mut()
+            .map(Option::as_mut)
+            .chain(self.stash.iter_mut().chain(self.overflow.iter_mut()).map(Some));
+        let mut next_position = 0;
+        for i in order {
+            values[i] = Some(&mut slots.nth(positions[i] - next_position)??.value);
+            next_position = positions[i] + 1;
+        }
+        Some(values.map(|value| value.expect("every position is visited")))
+    }
+
+    /// Applies `f` to the value stored for the key in place,
+    /// returning `true` if the key was stored.
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V),
+    {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns `true` if an entry is stored for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_location(key).is_some()
+    }
+
+    fn find_location<Q>(&self, key: &Q) -> Option<Location>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hashed_key = self.compute_hash(key);
+        self.locate(hashed_key, |stored| stored.borrow() == key)
+    }
+
+    // checks the two buckets of the hash, then the stash, then the overflow stash
+    fn locate(&self, hashed_key: u64, is_key: impl Fn(&K) -> bool) -> Option<Location> {
+        let matches = |bucket: &Bucket<K, V>| bucket.hashed_key == hashed_key && is_key(&bucket.key);
+
+        for idx in self.compute_bucket_indices(hashed_key, self.capacity()) {
+            if self.buckets[idx].as_ref().is_some_and(matches) {
+                return Some(Location::Bucket(idx));
+            }
+        }
+        if let Some(pos) = self.stash.iter().position(matches) {
+            return Some(Location::Stash(pos));
+        }
+        self.overflow.iter().position(matches).map(Location::Overflow)
+    }
+
+    fn bucket(&self, location: Location) -> &Bucket<K, V> {
+        match location {
+            Location::Bucket(idx) => self.buckets[idx].as_ref().expect("located buckets are live"),
+            Location::Stash(pos) => &self.stash[pos],
+            Location::Overflow(pos) => &self.overflow[pos],
+        }
+    }
+
+    fn bucket_mut(&mut self, location: Location) -> &mut Bucket<K, V> {
+        match location {
+            Location::Bucket(idx) => self.buckets[idx].as_mut().expect("located buckets are live"),
+            Location::Stash(pos) => &mut self.stash[pos],
+            Location::Overflow(pos) => &mut self.overflow[pos],
+        }
+    }
+
+    /// Returns the number of entries stored in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the table stores no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry, keeping the allocated buckets.
+    pub fn clear(&mut self) {
+        self.buckets.fill_with(|| None);
+        self.stash.clear();
+        self.overflow.clear();
+        self.len = 0;
+    }
+
+    /// Removes every entry, returning them through an iterator.
+    ///
+    /// The table keeps its number of buckets.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        let empty_buckets = Self::make_empty_buckets(self.capacity());
+        let buckets = mem::replace(&mut self.buckets, empty_buckets);
+        let stash = mem::take(&mut self.stash);
+        let overflow = mem::take(&mut self.overflow);
+        let remaining = mem::take(&mut self.len);
+        Drain {
+            inner: IntoIter { buckets: buckets.into_iter(), stash: stash.into_iter().chain(overflow), remaining },
+            marker: PhantomData,
+        }
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for slot in self.buckets.iter_mut() {
+            if let Some(bucket) = slot {
+                if !f(&bucket.key, &mut bucket.value) {
+                    *slot = None;
+                    self.len -= 1;
+                }
+            }
+        }
+        let before = self.stash.len() + self.overflow.len();
+        self.stash.retain_mut(|bucket| f(&bucket.key, &mut bucket.value));
+        self.overflow.retain_mut(|bucket| f(&bucket.key, &mut bucket.value));
+        self.len -= before - self.stash.len() - self.overflow.len();
+        self.unstash();
+    }
+
+    /// Returns an iterator removing and yielding the entries for which `pred` returns `true`.
+    ///
+    /// Entries are only removed as the iterator advances,
+    /// and stashed entries move into the freed buckets once the iterator is dropped.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, S, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf { table: self, idx: 0, pred }
+    }
+
+    /// Returns an iterator over the stored entries in bucket order, then the stashed ones.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored entries in bucket order, then the stashed ones,
+    /// with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the stored values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Returns an iterator over mutable references to the stored values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { inner: self.iter_mut() }
+    }
+
+    /// Consumes the table into an iterator over the stored keys.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys { inner: self.into_iter() }
+    }
+
+    /// Consumes the table into an iterator over the stored values.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues { inner: self.into_iter() }
+    }
+
+    /// Removes the entry stored for the key, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the entry stored for the key, returning the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let location = self.find_location(key)?;
+        let bucket = self.remove_at(location);
+        Some((bucket.key, bucket.value))
+    }
+
+    fn remove_at(&mut self, location: Location) -> Bucket<K, V> {
+        let bucket = match location {
+            Location::Bucket(idx) => {
+                let bucket = self.buckets[idx].take().expect("only live buckets are removed");
+                // a stashed entry may fit into the freed bucket
+                self.unstash();
+                bucket
+            },
+            Location::Stash(pos) => self.stash.swap_remove(pos),
+            Location::Overflow(pos) => self.overflow.swap_remove(pos),
+        };
+        self.len -= 1;
+        bucket
+    }
+}
+
+// the increment of the splitmix64 generator, 2^64 divided by the golden ratio
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl<K, V, S> Default for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for HashTable<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, S> PartialEq for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S> Eq for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    V: Eq,
+{
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hash_table = Self::default();
+        hash_table.extend(iter);
+        hash_table
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for HashTable<K, V, S>
+where
+    K: Hash + Eq + Copy,
+    S: BuildHasher,
+    V: Copy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, S, Q> Index<&Q> for HashTable<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    S: BuildHasher,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value stored for the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not stored.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key is not stored in the table")
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashTable<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        Iter { buckets: self.buckets.iter(), stash: self.stash.iter().chain(&self.overflow), remaining: self.len }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashTable<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        IterMut {
+            buckets: self.buckets.iter_mut(),
+            stash: self.stash.iter_mut().chain(&mut self.overflow),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<K, V, S> Map<K, V> for HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Iter<'a> = Iter<'a, K, V> where Self: 'a, K: 'a, V: 'a;
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove(key)
+    }
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn iter(&self) -> Iter<'_, K, V> {
+        self.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for HashTable<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consumes the table into an iterator over the stored entries in bucket order,
+    /// then the stashed ones.
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            buckets: self.buckets.into_iter(),
+            stash: self.stash.into_iter().chain(self.overflow),
+            remaining: self.len,
+        }
+    }
+}
+
+/// Iterator over the entries of a [`HashTable`], created by [`HashTable::iter`].
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Option<Bucket<K, V>>>,
+    stash: Stashes<slice::Iter<'a, Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.buckets.find_map(Option::as_ref).or_else(|| self.stash.next())?;
+        self.remaining -= 1;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Iterator over the entries of a [`HashTable`] with mutable references to the values,
+/// created by [`HashTable::iter_mut`].
+pub struct IterMut<'a, K, V> {
+    buckets: slice::IterMut<'a, Option<Bucket<K, V>>>,
+    stash: Stashes<slice::IterMut<'a, Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.buckets.find_map(Option::as_mut).or_else(|| self.stash.next())?;
+        self.remaining -= 1;
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owning iterator over the entries of a [`HashTable`], created by [`HashTable::into_iter`].
+pub struct IntoIter<K, V> {
+    buckets: vec::IntoIter<Option<Bucket<K, V>>>,
+    stash: Stashes<vec::IntoIter<Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.buckets.find_map(|slot| slot).or_else(|| self.stash.next())?;
+        self.remaining -= 1;
+        Some((bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+/// Draining iterator over the entries of a [`HashTable`], created by [`HashTable::drain`].
+pub struct Drain<'a, K, V> {
+    inner: IntoIter<K, V>,
+    marker: PhantomData<&'a mut HashTable<K, V>>,
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+/// Iterator removing the entries of a [`HashTable`] that match a predicate,
+/// created by [`HashTable::extract_if`].
+pub struct ExtractIf<'a, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    table: &'a mut HashTable<K, V, S>,
+    // counts the buckets first, then the stashed entries, then the overflowed ones
+    idx: usize,
+    pred: F,
+}
+
+impl<K, V, S, F> Iterator for ExtractIf<'_, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.table.capacity() {
+            let slot = &mut self.table.buckets[self.idx];
+            self.idx += 1;
+            if let Some(bucket) = slot {
+                if (self.pred)(&bucket.key, &mut bucket.value) {
+                    let bucket = slot.take()?;
+                    self.table.len -= 1;
+                    return Some((bucket.key, bucket.value));
+                }
+            }
+        }
+
+        // the stash no longer shrinks once the overflow stash is reached
+        for overflowed in [false, true] {
+            let start = if overflowed { self.table.capacity() + self.table.stash.len() } else { self.table.capacity() };
+            let stashed = if overflowed { &mut self.table.overflow } else { &mut self.table.stash };
+            while let Some(bucket) = stashed.get_mut(self.idx - start) {
+                if (self.pred)(&bucket.key, &mut bucket.value) {
+                    // the last stashed entry moves into idx, which is visited next
+                    let bucket = stashed.swap_remove(self.idx - start);
+                    self.table.len -= 1;
+                    return Some((bucket.key, bucket.value));
+                }
+                self.idx += 1;
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.table.len))
+    }
+}
+
+impl<K, V, S, F> Drop for ExtractIf<'_, K, V, S, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    fn drop(&mut self) {
+        // the entries stay put while iterating, so unstash only now
+        self.table.unstash();
+    }
+}
+
+/// Iterator over the keys of a [`HashTable`], created by [`HashTable::keys`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// Iterator over the values of a [`HashTable`], created by [`HashTable::values`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// Iterator over mutable references to the values of a [`HashTable`],
+/// created by [`HashTable::values_mut`].
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+/// Owning iterator over the keys of a [`HashTable`], created by [`HashTable::into_keys`].
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+/// Owning iterator over the values of a [`HashTable`], created by [`HashTable::into_values`].
+pub struct IntoValues<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+/// View into a single entry of a [`HashTable`], created by [`HashTable::entry`].
+pub enum Entry<'a, K, V, S = DefaultHashBuilder> {
+    /// The key is stored in the table.
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    /// The key is not stored in the table.
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// View into an entry whose key is stored in a [`HashTable`].
+pub struct OccupiedEntry<'a, K, V, S = DefaultHashBuilder> {
+    table: &'a mut HashTable<K, V, S>,
+    location: Location,
+}
+
+/// View into an entry whose key is not stored in a [`HashTable`] yet.
+pub struct VacantEntry<'a, K, V, S = DefaultHashBuilder> {
+    table: &'a mut HashTable<K, V, S>,
+    key: K,
+    hashed_key: u64,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the stored value, inserting `default` if the key is not stored.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the stored value, inserting the result of `default` if the key is not stored.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Returns the stored value, inserting the result of `default` for the key
+    /// if the key is not stored.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns the stored value, inserting `V::default()` if the key is not stored.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Applies `f` to the stored value if the key is stored.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Stores the value whether the key is stored or not.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            },
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the stored key.
+    pub fn key(&self) -> &K {
+        &self.table.bucket(self.location).key
+    }
+
+    /// Returns a reference to the stored value.
+    pub fn get(&self) -> &V {
+        &self.table.bucket(self.location).value
+    }
+
+    /// Returns a mutable reference to the stored value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.table.bucket_mut(self.location).value
+    }
+
+    /// Converts the entry into a mutable reference to the stored value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.bucket_mut(self.location).value
+    }
+
+    /// Replaces the stored value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry, returning the stored key and its value.
+    pub fn remove_entry(self) -> (K, V) {
+        let bucket = self.table.remove_at(self.location);
+        (bucket.key, bucket.value)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns the key that would be stored.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back the key without storing anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Stores the value for the key, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    /// Stores the value for the key, returning the now occupied entry.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        let table = self.table;
+        let location = table.insert_absent(Bucket {
+            key: self.key,
+            hashed_key: self.hashed_key,
+            value,
+        });
+        table.len += 1;
+        OccupiedEntry { table, location }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{shared_tests, CollidingKey, CountingHashBuilder};
+    use std::hash::Hasher;
+    use rand::prelude::*;
+    use std::collections::HashMap;
+
+    // checks that every entry sits in one of its two buckets or in the stash,
+    // which lookups rely on
+    fn assert_cuckoo_invariant<K: Hash + Eq, V, S: BuildHasher>(hash_table: &HashTable<K, V, S>) {
+        let len = hash_table.capacity();
+        for (idx, slot) in hash_table.buckets.iter().enumerate() {
+            if let Some(bucket) = slot {
+                assert!(hash_table.compute_bucket_indices(bucket.hashed_key, len).contains(&idx));
+            }
+        }
+        assert!(hash_table.stash.len() <= HashTable::<K, V, S>::MAX_STASH);
+    }
+
+    shared_tests!(assert_cuckoo_invariant);
+
+    #[test]
+    fn matches_a_model() {
+        // Setup
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let mut hash_table = HashTable::with_seed(0x5eed);
+        let mut model = HashMap::new();
+
+        // Exercise: interleave inserts and removals over a small key space
+        for _ in 0..10000 {
+            let key: u32 = rng.gen_range(0..500);
+            if rng.gen_bool(0.6) {
+                assert_eq!(model.insert(key, key * 2), hash_table.insert(key, key * 2));
+            } else {
+                assert_eq!(model.remove(&key), hash_table.remove(&key));
+            }
+        }
+
+        // Verify: the table matches the model and keeps every entry where lookups check
+        assert_eq!(model.len(), hash_table.len());
+        for (key, value) in &model {
+            assert_eq!(Some(value), hash_table.get(key));
+        }
+        assert_cuckoo_invariant(&hash_table);
+    }
+
+    #[test]
+    fn colliding_hashes_fill_the_stash() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        let max_stash = HashTable::<CollidingKey, u32>::MAX_STASH;
+
+        // Exercise: insert as many keys with identical hashes as two buckets and the stash hold
+        for i in 0..2 + max_stash as u32 {
+            hash_table.insert(CollidingKey(i), i);
+        }
+
+        // Verify: each key keeps its own value, and the stash took the overflow
+        for i in 0..2 + max_stash as u32 {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+        assert_eq!(max_stash, hash_table.stash.len());
+        assert_cuckoo_invariant(&hash_table);
+
+        // Exercise: remove a key from a bucket
+        let in_bucket = *hash_table.buckets.iter().flatten().next().map(|bucket| &bucket.key).unwrap();
+        hash_table.remove(&in_bucket);
+
+        // Verify: a stashed key moved into the freed bucket
+        assert_eq!(max_stash - 1, hash_table.stash.len());
+        assert!(hash_table.get(&in_bucket).is_none());
+        assert_eq!(1 + max_stash, hash_table.len());
+        assert_cuckoo_invariant(&hash_table);
+
+        // Exercise & Verify: disjoint keys across buckets and the stash are borrowed together
+        let keys: Vec<CollidingKey> = hash_table.keys().copied().collect();
+        let [a, b] = hash_table.get_many_mut([&keys[keys.len() - 1], &keys[0]]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(Some(&keys[0].0), hash_table.get(&keys[keys.len() - 1]));
+        assert_eq!(Some(&keys[keys.len() - 1].0), hash_table.get(&keys[0]));
+    }
+
+    // keys of one group hash alike, so no seeds separate them
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct GroupedKey(u32, u32);
+
+    impl Hash for GroupedKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    #[test]
+    fn colliding_hashes_overflow_the_stash() {
+        // Setup: more groups of keys sharing a hash than the stash holds
+        let mut hash_table = HashTable::with_seed(0x5eed);
+        let keys: Vec<GroupedKey> = (0..10).flat_map(|group| (0..8).map(move |i| GroupedKey(group, i))).collect();
+
+        // Exercise: insert every key
+        for (value, key) in keys.iter().enumerate() {
+            assert_eq!(None, hash_table.insert(*key, value));
+        }
+
+        // Verify: every key is stored and found, and the table grew by the load factor only
+        assert_eq!(keys.len(), hash_table.len());
+        for (value, key) in keys.iter().enumerate() {
+            assert_eq!(Some(&value), hash_table.get(key));
+        }
+        assert!(!hash_table.overflow.is_empty());
+        assert_eq!(256, hash_table.capacity());
+        assert_cuckoo_invariant(&hash_table);
+
+        // Exercise: remove every other key and grow the table
+        for key in keys.iter().step_by(2) {
+            assert!(hash_table.remove(key).is_some());
+        }
+        hash_table.set_max_load_factor(0.1);
+
+        // Verify: the remaining keys are still found
+        assert_eq!(keys.len() / 2, hash_table.len());
+        for (value, key) in keys.iter().enumerate() {
+            assert_eq!((value % 2 == 1).then_some(&value), hash_table.get(key));
+        }
+        assert_eq!(keys.len() / 2, hash_table.iter().count());
+        assert_cuckoo_invariant(&hash_table);
+
+        // Exercise & Verify: a whole group is borrowed together and extracted
+        let group: Vec<GroupedKey> = (1..8).step_by(2).map(|i| GroupedKey(0, i)).collect();
+        let values = hash_table.get_many_mut([&group[0], &group[1], &group[2], &group[3]]).unwrap();
+        assert_eq!([1, 3, 5, 7], values.map(|value| *value));
+        let mut extracted: Vec<GroupedKey> = hash_table.extract_if(|key, _| key.0 == 0).map(|(key, _)| key).collect();
+        extracted.sort_unstable_by_key(|key| key.1);
+        assert_eq!(group, extracted);
+        assert_eq!(keys.len() / 2 - group.len(), hash_table.len());
+        assert_cuckoo_invariant(&hash_table);
+    }
+
+    #[test]
+    fn reseeding_reuses_cached_hashes() {
+        // Setup: a load factor that makes eviction cycles, and so reseeds, frequent
+        let hash_builder = CountingHashBuilder::default();
+        let mut hash_table = HashTable::with_hasher(hash_builder.clone());
+        hash_table.set_max_load_factor(0.9);
+        let seeds = hash_table.seeds;
+
+        // Exercise: grow through several rehashes
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: every key was hashed once, on insertion, though the seeds changed
+        assert_ne!(seeds, hash_table.seeds);
+        assert_eq!(1000, hash_builder.hashes());
+        assert_cuckoo_invariant(&hash_table);
+
+        // Exercise: rehash in every other way
+        hash_table.set_max_load_factor(0.25);
+        hash_table.retain(|key, _| key % 2 == 0);
+        hash_table.extract_if(|key, _| key % 3 == 0).for_each(drop);
+        let cloned = hash_table.clone();
+
+        // Verify: no key was hashed again
+        assert_eq!(1000, hash_builder.hashes());
+        assert_eq!(333, cloned.len());
+        for i in (0..1000).filter(|i| i % 2 == 0 && i % 3 != 0) {
+            assert_eq!(Some(&i), cloned.get(&i));
+        }
+    }
+}
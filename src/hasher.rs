@@ -0,0 +1,127 @@
+//! Builders of the hashers used by the hash tables,
+//! and the mapping of their hashes to buckets.
+
+use std::hash::{BuildHasher, DefaultHasher, Hasher, RandomState};
+
+/// Builder of the hashers used by the hash tables unless another [`BuildHasher`] is given.
+///
+/// By default every builder draws random keys, so that keys colliding in one table
+/// cannot be precomputed to collide in another.
+/// [`DefaultHashBuilder::with_seed`] makes the hashes reproducible across runs
+/// of programs built with the same Rust toolchain instead.
+#[derive(Clone, Debug)]
+pub struct DefaultHashBuilder(Keys);
+
+#[derive(Clone, Debug)]
+enum Keys {
+    Random(RandomState),
+    Seeded(u64),
+}
+
+impl DefaultHashBuilder {
+    /// Creates a builder with random keys.
+    pub fn new() -> Self {
+        DefaultHashBuilder(Keys::Random(RandomState::new()))
+    }
+
+    /// Creates a builder whose hashers always produce the same hashes for the same seed.
+    ///
+    /// The hashes can be predicted by whoever knows the seed,
+    /// so this is meant for reproducible tests and benchmarks rather than untrusted keys.
+    ///
+    /// The hashes come from [`DefaultHasher`], whose algorithm may change between
+    /// Rust releases, so they only repeat for programs built with the same toolchain.
+    pub fn with_seed(seed: u64) -> Self {
+        DefaultHashBuilder(Keys::Seeded(seed))
+    }
+}
+
+impl Default for DefaultHashBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildHasher for DefaultHashBuilder {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        match &self.0 {
+            Keys::Random(random_state) => random_state.build_hasher(),
+            Keys::Seeded(seed) => {
+                let mut hasher = DefaultHasher::new();
+                hasher.write_u64(*seed);
+                hasher
+            }
+        }
+    }
+}
+
+// 2^64 divided by the golden ratio, whose multiples spread consecutive hashes far apart
+const FIBONACCI_MULTIPLIER: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// maps the hash to an index below capacity, a power of two, by fibonacci hashing:
+// the multiplication mixes every bit of the hash into the high bits,
+// which are then shifted down in place of a modulo
+pub(crate) fn fibonacci_index(hashed_key: u64, capacity: usize) -> usize {
+    debug_assert!(capacity.is_power_of_two());
+    let bits = capacity.trailing_zeros();
+    // shifting a u64 by 64 overflows, and a single bucket needs no bits anyway
+    if bits == 0 {
+        return 0;
+    }
+    (hashed_key.wrapping_mul(FIBONACCI_MULTIPLIER) >> (u64::BITS - bits)) as usize
+}
+
+// scrambles every bit of the input into every bit of the output, as splitmix64 does
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_keys_differ_between_builders() {
+        // Setup
+        let first = DefaultHashBuilder::new();
+        let second = DefaultHashBuilder::new();
+
+        // Verify: each builder is consistent with itself but not with the others
+        assert_eq!(first.hash_one("key"), first.hash_one("key"));
+        assert_eq!(first.hash_one("key"), first.clone().hash_one("key"));
+        assert!((0..8).any(|i| first.hash_one(i) != second.hash_one(i)));
+    }
+
+    #[test]
+    fn seeded_keys_are_reproducible() {
+        // Setup
+        let first = DefaultHashBuilder::with_seed(42);
+        let second = DefaultHashBuilder::with_seed(42);
+        let other = DefaultHashBuilder::with_seed(43);
+
+        // Verify
+        assert_eq!(first.hash_one("key"), second.hash_one("key"));
+        assert!((0..8).any(|i| first.hash_one(i) != other.hash_one(i)));
+    }
+
+    #[test]
+    fn fibonacci_index_spreads_weak_hashes() {
+        // Setup: hashes differing only in their high bits, which a mask would discard
+        let capacity = 64;
+        let hashes = (0..capacity as u64).map(|i| i << 48);
+
+        // Exercise
+        let mut indices: Vec<usize> = hashes.map(|hash| fibonacci_index(hash, capacity)).collect();
+
+        // Verify: every index is in range, and most buckets are used
+        assert!(indices.iter().all(|&idx| idx < capacity));
+        indices.sort();
+        indices.dedup();
+        assert!(indices.len() > capacity / 2);
+        assert_eq!(0, fibonacci_index(u64::MAX, 1));
+    }
+}
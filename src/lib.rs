@@ -1,2 +1,14 @@
-mod openaddressing;
-mod closedaddressing;
+#![doc = include_str!("../README.md")]
+
+pub mod closedaddressing;
+pub mod cuckoo;
+pub mod hasher;
+pub mod map;
+pub mod openaddressing;
+pub mod prelude;
+#[cfg(test)]
+mod testing;
+
+pub use closedaddressing as chained;
+pub use map::Map;
+pub use openaddressing as open;
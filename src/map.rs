@@ -0,0 +1,150 @@
+//! Interface shared by the hash tables of this crate.
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// Operations every hash table of this crate supports,
+/// whatever strategy it uses to resolve collisions.
+///
+/// Code written against `Map` can switch the strategy through a type parameter:
+///
+/// ```
+/// use hashtable_rs::prelude::*;
+///
+/// fn count_words<M: Map<String, usize>>(mut counts: M, text: &str) -> M {
+///     for word in text.split_whitespace() {
+///         match counts.get_mut(word) {
+///             Some(count) => *count += 1,
+///             None => {
+///                 counts.insert(word.to_string(), 1);
+///             }
+///         }
+///     }
+///     counts
+/// }
+///
+/// let open = count_words(OpenHashTable::new(), "a b a");
+/// let chained = count_words(ChainedHashTable::new(), "a b a");
+/// assert_eq!(open.get("a"), chained.get("a"));
+/// ```
+pub trait Map<K, V> {
+    /// Iterator over the stored entries.
+    type Iter<'a>: Iterator<Item = (&'a K, &'a V)>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    /// Inserts the value, returning the value it replaced if the key was already stored.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    /// Returns a reference to the value stored for the key.
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// Returns a mutable reference to the value stored for the key.
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// Removes the entry stored for the key, returning its value.
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// Returns `true` if an entry is stored for the key.
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns the number of stored entries.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no entries are stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry.
+    fn clear(&mut self);
+
+    /// Returns an iterator over the stored entries.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::closedaddressing;
+    use crate::cuckoo;
+    use crate::openaddressing;
+
+    fn exercise<M: Map<String, i32>>(mut map: M) {
+        // Exercise: insert
+        for i in 1..100 {
+            assert_eq!(None, map.insert(format!("key{}", i), i));
+        }
+
+        // Verify: get, contains_key and len
+        for i in 1..100 {
+            let key = format!("key{}", i);
+            assert_eq!(Some(&i), map.get(key.as_str()));
+            assert!(map.contains_key(key.as_str()));
+        }
+        assert!(!map.contains_key("key100"));
+        assert_eq!(99, map.len());
+
+        // Exercise: update through get_mut
+        for i in 1..100 {
+            let key = format!("key{}", i);
+            *map.get_mut(key.as_str()).unwrap() *= 2;
+        }
+
+        // Verify: iter yields every updated entry once
+        let mut entries: Vec<(String, i32)> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        entries.sort_by_key(|(_, v)| *v);
+        let expected: Vec<(String, i32)> = (1..100).map(|i| (format!("key{}", i), i * 2)).collect();
+        assert_eq!(expected, entries);
+
+        // Exercise: remove
+        for i in 1..50 {
+            assert_eq!(Some(i * 2), map.remove(format!("key{}", i).as_str()));
+        }
+        assert_eq!(None, map.remove("key1"));
+
+        // Verify: removed entries are gone
+        assert_eq!(50, map.len());
+        assert!(map.get("key1").is_none());
+        assert_eq!(Some(&100), map.get("key50"));
+
+        // Exercise: clear
+        map.clear();
+
+        // Verify: nothing is left
+        assert!(map.is_empty());
+        assert_eq!(0, map.iter().count());
+    }
+
+    #[test]
+    fn open_addressing_implements_map() {
+        exercise(openaddressing::HashTable::new());
+    }
+
+    #[test]
+    fn closed_addressing_implements_map() {
+        exercise(closedaddressing::HashTable::new());
+    }
+
+    #[test]
+    fn cuckoo_hashing_implements_map() {
+        exercise(cuckoo::HashTable::new());
+    }
+}
@@ -1,234 +1,2088 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
-use rand::prelude::*;
+//! Hash table resolving collisions by open addressing.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Index;
+use std::slice;
+use std::vec;
+use crate::hasher::{fibonacci_index, mix, DefaultHashBuilder};
+use crate::map::Map;
 use std::cmp;
 
 #[derive(Debug, Clone)]
-struct Bucket {
-    key: String,
+struct Bucket<K, V> {
+    key: K,
     hashed_key: u64,
-    value: i32,
-    deleted: bool,
+    value: V,
+}
+
+#[derive(Debug, Clone)]
+enum Slot<K, V> {
+    // never held an entry since the last rehash, which ends every probe sequence
+    Empty,
+    // held an entry that was removed, which probe sequences pass over
+    Deleted,
+    Occupied(Bucket<K, V>),
+}
+
+impl<K, V> Slot<K, V> {
+    fn bucket(&self) -> Option<&Bucket<K, V>> {
+        match self {
+            Slot::Occupied(bucket) => Some(bucket),
+            _ => None,
+        }
+    }
+
+    fn bucket_mut(&mut self) -> Option<&mut Bucket<K, V>> {
+        match self {
+            Slot::Occupied(bucket) => Some(bucket),
+            _ => None,
+        }
+    }
+
+    fn into_bucket(self) -> Option<Bucket<K, V>> {
+        match self {
+            Slot::Occupied(bucket) => Some(bucket),
+            _ => None,
+        }
+    }
 }
 
+type Buckets<K, V> = Vec<Slot<K, V>>;
+
 // Implementation of OpenAddressing
-#[derive(Debug)]
-struct HashTable {
-    buckets: Vec<Option<Bucket>>,
+/// Hash table storing its entries directly in a bucket array.
+///
+/// Colliding keys are placed into the buckets visited by the [`ProbeStrategy`] `P`,
+/// the following ones by default, wrapping around the end of the array,
+/// and deleted entries are left as tombstones until the table compacts itself.
+/// The table rehashes when a key finds no bucket within the maximum probe
+/// length or when the load factor exceeds its maximum,
+/// and the number of buckets grows as decided by the [`GrowthPolicy`] `G`.
+///
+/// In Robin Hood mode, enabled by [`HashTable::set_robin_hood`] on linearly probed tables, an inserted entry
+/// takes the bucket of any entry closer to its own initial bucket, which keeps every
+/// probe sequence short without a maximum probe length,
+/// and removed entries are replaced by shifting the following ones back instead of by tombstones.
+/// Keys are hashed by hashers built by the [`BuildHasher`] `S`.
+#[derive(Clone)]
+pub struct HashTable<K, V, S = DefaultHashBuilder, G = Doubling, P = Linear> {
+    buckets: Buckets<K, V>,
+    len: usize,
+    tombstones: usize,
+    hash_builder: S,
+    growth_policy: G,
+    probe_strategy: P,
+    max_probe: usize,
+    max_load_factor: f64,
+    robin_hood: bool,
 }
 
-impl HashTable {
-    const INITIAL_SIZE: usize = 16;
-    const MAX_PROBE: usize = 4;
+/// Decides the number of buckets of a [`HashTable`] after it rehashes,
+/// and how hashes are mapped to bucket indices.
+pub trait GrowthPolicy {
+    /// Returns the number of buckets to try when `capacity` buckets are not enough.
+    ///
+    /// The number must be a larger power of two, like the initial number of buckets,
+    /// so that bucket indices can be computed without a division;
+    /// tables panic on any other number.
+    fn next_capacity(&self, capacity: usize) -> usize;
+
+    /// Maps the hash to a bucket index below `capacity`, a power of two.
+    ///
+    /// The default takes the high bits of the hash after fibonacci hashing,
+    /// which spreads keys well even when the hasher leaves its low bits weak.
+    fn bucket_index(&self, hashed_key: u64, capacity: usize) -> usize {
+        fibonacci_index(hashed_key, capacity)
+    }
+}
+
+/// Doubles the number of buckets on every growth.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn next_capacity(&self, capacity: usize) -> usize {
+        capacity * 2
+    }
+}
+
+/// Grows to the next power of two, which every capacity is now, just as [`Doubling`] does.
+#[deprecated(note = "every capacity is a power of two now, so use `Doubling` instead")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PowerOfTwo;
+
+#[allow(deprecated)]
+impl GrowthPolicy for PowerOfTwo {
+    fn next_capacity(&self, capacity: usize) -> usize {
+        Doubling.next_capacity(capacity)
+    }
+}
+
+/// Decides which buckets of a [`HashTable`] are probed for a key, and in which order.
+pub trait ProbeStrategy {
+    /// Returns the offset from the initial bucket of the `i`-th bucket probed for the hash,
+    /// among `capacity` buckets, a power of two.
+    ///
+    /// The offsets for `i` below `capacity` should cover every bucket,
+    /// so that a key finds a bucket whenever one is free within the maximum probe length.
+    fn offset(&self, hashed_key: u64, i: usize, capacity: usize) -> usize;
+}
+
+/// Probes the buckets following the initial one.
+///
+/// Neighboring probe sequences overlap the most, but each probe stays in the same cache line longer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Linear;
+
+impl ProbeStrategy for Linear {
+    fn offset(&self, _hashed_key: u64, i: usize, _capacity: usize) -> usize {
+        i
+    }
+}
+
+/// Probes buckets at triangular-number offsets from the initial one,
+/// which visit every bucket of a power-of-two capacity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quadratic;
+
+impl ProbeStrategy for Quadratic {
+    fn offset(&self, _hashed_key: u64, i: usize, _capacity: usize) -> usize {
+        // halving the even factor first keeps the wrapped product right modulo the capacity
+        if i.is_multiple_of(2) {
+            (i / 2).wrapping_mul(i + 1)
+        } else {
+            i.wrapping_mul(i / 2 + 1)
+        }
+    }
+}
+
+/// Probes buckets in steps whose size is derived from the hash as well,
+/// so that keys sharing an initial bucket still follow different sequences.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoubleHashing;
+
+impl ProbeStrategy for DoubleHashing {
+    fn offset(&self, hashed_key: u64, i: usize, _capacity: usize) -> usize {
+        // the step comes from the scrambled hash, so that it is unrelated to the initial bucket
+        // whichever bits the growth policy takes, and an odd step visits every bucket
+        // of a power-of-two capacity
+        let step = (mix(hashed_key) as usize) | 1;
+        i.wrapping_mul(step)
+    }
+}
 
+impl<K, V> HashTable<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty table with the initial number of buckets.
     pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table with enough buckets to hold `capacity` entries
+    /// without exceeding the maximum load factor.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+
+    /// Creates an empty table whose hashes, and therefore layout, are the same
+    /// for every table created with the same seed by a program built with the same toolchain.
+    ///
+    /// Tables created by [`HashTable::new`] draw random keys instead,
+    /// which keeps crafted keys from colliding on purpose.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_hasher(DefaultHashBuilder::with_seed(seed))
+    }
+}
+
+impl<K, V, S> HashTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`,
+    /// with enough buckets to hold `capacity` entries without exceeding the maximum load factor.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self::with_capacity_hasher_and_growth_policy(capacity, hash_builder, Doubling)
+    }
+}
+
+impl<K, V, G> HashTable<K, V, DefaultHashBuilder, G>
+where
+    K: Hash + Eq,
+    G: GrowthPolicy,
+{
+    /// Creates an empty table growing as decided by `growth_policy`.
+    pub fn with_growth_policy(growth_policy: G) -> Self {
+        Self::with_capacity_hasher_and_growth_policy(0, DefaultHashBuilder::default(), growth_policy)
+    }
+}
+
+impl<K, V, P> HashTable<K, V, DefaultHashBuilder, Doubling, P>
+where
+    K: Hash + Eq,
+    P: ProbeStrategy,
+{
+    /// Creates an empty table probing the buckets as decided by `probe_strategy`.
+    pub fn with_probe_strategy(probe_strategy: P) -> Self {
+        Self::with_parts(0, DefaultHashBuilder::default(), Doubling, probe_strategy)
+    }
+}
+
+impl<K, V, S, G> HashTable<K, V, S, G>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+{
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`
+    /// and growing as decided by `growth_policy`,
+    /// with enough buckets to hold `capacity` entries without exceeding the maximum load factor.
+    pub fn with_capacity_hasher_and_growth_policy(capacity: usize, hash_builder: S, growth_policy: G) -> Self {
+        Self::with_parts(capacity, hash_builder, growth_policy, Linear)
+    }
+
+    /// Returns `true` if the table is in Robin Hood mode.
+    pub fn robin_hood(&self) -> bool {
+        self.robin_hood
+    }
+
+    /// Switches Robin Hood mode on or off, rearranging the stored entries for it.
+    ///
+    /// Robin Hood mode relies on linear probing, so only linearly probed tables offer it.
+    pub fn set_robin_hood(&mut self, robin_hood: bool) {
+        if self.robin_hood != robin_hood {
+            self.robin_hood = robin_hood;
+            self.rehash_to(self.capacity());
+        }
+    }
+}
+
+impl<K, V, S, G, P> HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    const INITIAL_SIZE: usize = 16;
+    const DEFAULT_MAX_PROBE: usize = 4;
+    const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.75;
+    // the table compacts itself once tombstones fill this share of the buckets
+    const MAX_TOMBSTONE_RATIO: f64 = 0.25;
+
+    /// Creates an empty table hashing keys with hashers built by `hash_builder`,
+    /// growing as decided by `growth_policy` and probing as decided by `probe_strategy`,
+    /// with enough buckets to hold `capacity` entries without exceeding the maximum load factor.
+    pub fn with_parts(capacity: usize, hash_builder: S, growth_policy: G, probe_strategy: P) -> Self {
+        let mut hash_table = Self {
+            buckets: Vec::new(),
+            len: 0,
+            tombstones: 0,
+            hash_builder,
+            growth_policy,
+            probe_strategy,
+            max_probe: Self::DEFAULT_MAX_PROBE,
+            max_load_factor: Self::DEFAULT_MAX_LOAD_FACTOR,
+            robin_hood: false,
+        };
+        let len = hash_table.capacity_to_fit(Self::INITIAL_SIZE, capacity);
+        hash_table.buckets = Self::make_empty_buckets(len);
+        hash_table
+    }
+
+    /// Returns a reference to the builder of the hashers of the table.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the number of buckets probed for a key before the table rehashes.
+    ///
+    /// The table only lengthens it by itself when more keys share one hash
+    /// than it probes, as no number of buckets would separate them.
+    /// Tables in Robin Hood mode ignore it and rehash on the load factor only.
+    pub fn max_probe(&self) -> usize {
+        self.max_probe
+    }
+
+    /// Sets the number of buckets probed for a key before the table rehashes,
+    /// rearranging the stored entries to respect it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_probe` is zero.
+    pub fn set_max_probe(&mut self, max_probe: usize) {
+        assert!(max_probe > 0, "max probe must be positive");
+        self.max_probe = max_probe;
+        self.rehash_to(self.capacity());
+    }
+
+    /// Returns the load factor above which the table rehashes.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Sets the load factor above which the table rehashes,
+    /// growing the table right away if it no longer fits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_load_factor` is not in `(0, 1]`.
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) {
+        assert!(max_load_factor > 0.0 && max_load_factor <= 1.0, "max load factor must be in (0, 1]");
+        self.max_load_factor = max_load_factor;
+
+        let next_len = self.capacity_to_fit(self.capacity(), self.len);
+        if next_len != self.capacity() {
+            self.rehash_to(next_len);
+        }
+    }
+
+    // grows the number of buckets from len until entries fit in the maximum load factor
+    fn capacity_to_fit(&self, len: usize, entries: usize) -> usize {
+        let mut next_len = len;
+        while entries as f64 > self.max_load_factor * next_len as f64 {
+            next_len = self.next_capacity(next_len);
+        }
+        next_len
+    }
+
+    // asks the growth policy for the number of buckets after len,
+    // which bucket indexing and probing need to be a power of two
+    fn next_capacity(&self, len: usize) -> usize {
+        let next_len = self.growth_policy.next_capacity(len);
+        assert!(next_len > len && next_len.is_power_of_two(), "growth policy must return a larger power of two");
+        next_len
+    }
+
+    fn make_empty_buckets(len: usize) -> Buckets<K, V> {
         let mut buckets = Vec::new();
-        buckets.resize(Self::INITIAL_SIZE, None);
-        Self { buckets }
+        buckets.resize_with(len, || Slot::Empty);
+        buckets
+    }
+
+    /// Inserts the value, returning the value it replaced if the key was already stored.
+    ///
+    /// The stored key is kept when its value is replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            // replace value when the key is already stored
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
     }
 
-    pub fn upsert(&mut self, key: String, value: i32) {
+    /// Returns the entry of the key for in-place manipulation,
+    /// hashing the key and probing the buckets only once.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, G, P> {
         let hashed_key = self.compute_hash(&key);
         loop {
-            match self.compute_insertable_index(hashed_key, &self.buckets) {
+            match self.compute_insertable_index(&key, hashed_key, &self.buckets) {
                 // rehash if no insertable index
                 // then re-compute insertable index
                 None => {
+                    self.make_room(hashed_key);
+                },
+                // the key is already stored
+                Some(idx) if self.buckets[idx].bucket().is_some_and(|bucket| bucket.hashed_key == hashed_key && bucket.key == key) => {
+                    return Entry::Occupied(OccupiedEntry { table: self, idx });
+                },
+                // rehash ahead when the new entry would crowd the table
+                Some(_) if (self.len + 1) as f64 > self.max_load_factor * self.capacity() as f64 => {
                     self.rehash();
                 },
-                // insert value when found insertable index
                 Some(idx) => {
-                    self.buckets[idx] = Some(Bucket{
-                        key: key.clone(),
-                        hashed_key: hashed_key,
-                        value: value,
-                        deleted: false,
-                    });
-                    return;
+                    return Entry::Vacant(VacantEntry { table: self, key, hashed_key, idx });
                 }
             }
         }
     }
 
-    fn compute_insertable_index(&self, hashed_key: u64, buckets: &Vec<Option<Bucket>>) -> Option<usize> {
-        let idx = self.compute_bucket_index(hashed_key, buckets.len());
-        
-        for i in idx..cmp::min(idx + Self::MAX_PROBE, buckets.len()) {
+    // returns the bucket holding the key if it is stored,
+    // otherwise the first tombstone or empty bucket of the probe sequence,
+    // or in Robin Hood mode the first bucket whose entry is closer to its initial bucket
+    fn compute_insertable_index(&self, key: &K, hashed_key: u64, buckets: &[Slot<K, V>]) -> Option<usize> {
+        let mut first_deleted = None;
+
+        for (distance, i) in self.probe_sequence(hashed_key, buckets.len()).enumerate() {
             match &buckets[i] {
-                // insert value when the bucket is empty
-                None => {
-                    return Some(i);
+                // no bucket after an empty one holds the key,
+                // so reuse a preceding tombstone or take the empty bucket
+                Slot::Empty => {
+                    return first_deleted.or(Some(i));
+                },
+                // remember the first tombstone but keep looking for the key
+                Slot::Deleted => {
+                    first_deleted = first_deleted.or(Some(i));
                 },
                 // update value when same key is specified
-                Some(bucket) if bucket.hashed_key == hashed_key || bucket.deleted => {
+                Slot::Occupied(bucket) if bucket.hashed_key == hashed_key && bucket.key == *key => {
                     return Some(i)
                 },
-                // insert value to the first empty bucket when hash value collides
-                Some(_) => {}
+                // no entry after a closer one holds the key, so take its bucket
+                Slot::Occupied(bucket) if self.robin_hood && self.probe_distance(bucket.hashed_key, i, buckets.len()) < distance => {
+                    return Some(i);
+                },
+                // continue when hash value collides
+                Slot::Occupied(_) => {}
             }
         }
-        return None;
+        first_deleted
+    }
+
+    /// Returns a reference to the value stored for the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        self.buckets[idx].bucket().map(|bucket| &bucket.value)
+    }
+
+    /// Returns a mutable reference to the value stored for the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        self.buckets[idx].bucket_mut().map(|bucket| &mut bucket.value)
+    }
+
+    /// Returns the stored key and a reference to its value.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        self.buckets[idx].bucket().map(|bucket| (&bucket.key, &bucket.value))
+    }
+
+    /// Returns mutable references to the values stored for `N` distinct keys at once.
+    ///
+    /// Returns `None` if any of the keys is not stored or the same key is given twice.
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut indices = [0; N];
+        for (idx, key) in indices.iter_mut().zip(keys) {
+            *idx = self.find_index(key)?;
+        }
+        // equal keys share an index, which get_disjoint_mut refuses
+        let slots = self.buckets.get_disjoint_mut(indices).ok()?;
+        Some(slots.map(|slot| &mut slot.bucket_mut().expect("found keys are live").value))
+    }
+
+    /// Applies `f` to the value stored for the key in place,
+    /// returning `true` if the key was stored.
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V),
+    {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns `true` if an entry is stored for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_index(key).is_some()
     }
 
-    pub fn get(&self, key: &str) -> Option<i32> {
+    fn find_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         let hashed_key = self.compute_hash(key);
-        let idx = self.compute_bucket_index(hashed_key, self.len());
-        
-        for i in idx..cmp::min(idx + Self::MAX_PROBE, self.len()) {
+
+        for (distance, i) in self.probe_sequence(hashed_key, self.capacity()).enumerate() {
             match &self.buckets[i] {
                 // return None when reach empty bucket
-                None => {
+                Slot::Empty => {
                     return None;
                 },
-                // return Some when reach non empty bucket and hashed key is identical
-                Some(bucket) if bucket.hashed_key == hashed_key && !bucket.deleted => {
-                    return Some(bucket.value);
+                // return index when reach non empty bucket and key is identical
+                Slot::Occupied(bucket) if bucket.hashed_key == hashed_key && bucket.key.borrow() == key => {
+                    return Some(i);
+                },
+                // return None when reach an entry closer to its initial bucket in Robin Hood mode
+                Slot::Occupied(bucket) if self.robin_hood && self.probe_distance(bucket.hashed_key, i, self.capacity()) < distance => {
+                    return None;
                 },
-                // continue when reach non empty bucket but hashed key is not identical
-                Some(_) => {}
+                // continue when reach a tombstone or a bucket whose key is not identical
+                _ => {}
             }
         }
-        return None;
+        None
     }
 
-    fn len(&self) -> usize {
+    /// Returns the number of entries stored in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the table stores no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry, keeping the allocated buckets.
+    pub fn clear(&mut self) {
+        self.buckets.fill_with(|| Slot::Empty);
+        self.len = 0;
+        self.tombstones = 0;
+    }
+
+    /// Removes every entry, returning them through an iterator.
+    ///
+    /// The table keeps its number of buckets.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        let empty_buckets = Self::make_empty_buckets(self.capacity());
+        let buckets = mem::replace(&mut self.buckets, empty_buckets);
+        let remaining = mem::take(&mut self.len);
+        self.tombstones = 0;
+        Drain { inner: IntoIter { buckets: buckets.into_iter(), remaining }, marker: PhantomData }
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    ///
+    /// Removed entries leave no tombstones behind.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut removed = 0;
+        for slot in self.buckets.iter_mut() {
+            if let Slot::Occupied(bucket) = slot {
+                if !f(&bucket.key, &mut bucket.value) {
+                    *slot = Slot::Deleted;
+                    removed += 1;
+                }
+            }
+        }
+        self.len -= removed;
+
+        // rearrange the remaining entries instead of leaving tombstones
+        if removed > 0 {
+            self.compact();
+        }
+    }
+
+    /// Returns an iterator removing and yielding the entries for which `pred` returns `true`.
+    ///
+    /// Entries are only removed as the iterator advances,
+    /// and the table drops the tombstones they leave once the iterator is dropped.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, S, G, P, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf { table: self, idx: 0, removed: 0, pred }
+    }
+
+    /// Drops every tombstone by rearranging the entries within the current buckets.
+    ///
+    /// The table also compacts itself once tombstones take up
+    /// a quarter of the buckets.
+    pub fn compact(&mut self) {
+        self.rehash_to(self.capacity());
+    }
+
+    /// Returns an iterator over the stored entries in bucket order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored entries in bucket order,
+    /// with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the stored keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the stored values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Returns an iterator over mutable references to the stored values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { inner: self.iter_mut() }
+    }
+
+    /// Consumes the table into an iterator over the stored keys.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys { inner: self.into_iter() }
+    }
+
+    /// Consumes the table into an iterator over the stored values.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues { inner: self.into_iter() }
+    }
+
+    /// Returns the number of buckets, live or not.
+    pub fn capacity(&self) -> usize {
         self.buckets.len()
     }
 
-    fn compute_hash(&self, key: &str) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish()
+    /// Returns the ratio of stored entries to buckets.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    fn compute_hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
     }
 
     fn compute_bucket_index(&self, hashed_key: u64, len: usize) -> usize {
-        (hashed_key % (len as u64)) as usize
+        self.growth_policy.bucket_index(hashed_key, len)
     }
 
-    fn rehash(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut next_len = self.len() + rng.gen::<usize>() % self.len();
+    // yields the indices of the buckets probed for the hash,
+    // wrapping around the end of the buckets
+    fn probe_sequence(&self, hashed_key: u64, len: usize) -> impl Iterator<Item = usize> + '_ {
+        let idx = self.compute_bucket_index(hashed_key, len);
+        // Robin Hood mode stops probing by distance rather than by a maximum
+        let max_probe = if self.robin_hood { len } else { cmp::min(self.max_probe, len) };
+        (0..max_probe).map(move |i| idx.wrapping_add(self.probe_strategy.offset(hashed_key, i, len)) & (len - 1))
+    }
+
+    // returns how far the bucket idx is from the initial bucket of the hash,
+    // counted in linear probes as in Robin Hood mode
+    fn probe_distance(&self, hashed_key: u64, idx: usize, len: usize) -> usize {
+        idx.wrapping_sub(self.compute_bucket_index(hashed_key, len)) & (len - 1)
+    }
+
+    // places the bucket at the first bucket from idx that is empty or whose entry
+    // is closer to its initial bucket, carrying the displaced entry on in the same way
+    fn shift_in(&self, buckets: &mut [Slot<K, V>], idx: usize, bucket: Bucket<K, V>) {
+        let len = buckets.len();
+        let mut carried = bucket;
+        let mut i = idx;
         loop {
-            match self.make_rehashed_buckets(next_len) {
-                None => {
-                    next_len += rng.gen::<usize>() % self.len();
-                },
-                Some(new_buckets) => {
-                    self.buckets = new_buckets;
+            match &mut buckets[i] {
+                slot @ (Slot::Empty | Slot::Deleted) => {
+                    *slot = Slot::Occupied(carried);
                     return;
+                },
+                Slot::Occupied(resident) => {
+                    if self.probe_distance(resident.hashed_key, i, len) < self.probe_distance(carried.hashed_key, i, len) {
+                        mem::swap(resident, &mut carried);
+                    }
                 }
             }
+            i = (i + 1) & (len - 1);
+        }
+    }
+
+    // makes room for a key whose probe sequence is full, lengthening the probe
+    // when keys sharing its hash fill it, as growing would not separate them
+    fn make_room(&mut self, hashed_key: u64) {
+        let len = self.capacity();
+        let shared = self
+            .probe_sequence(hashed_key, len)
+            .all(|i| matches!(&self.buckets[i], Slot::Occupied(bucket) if bucket.hashed_key == hashed_key));
+        if shared && self.max_probe < len {
+            self.max_probe *= 2;
+        } else {
+            self.rehash();
         }
     }
 
-    fn make_rehashed_buckets(&self, next_len: usize) -> Option<Vec<Option<Bucket>>> {
-        let mut new_buckets: Vec<Option<Bucket>> = Vec::new();
-        new_buckets.resize(next_len, None);
-        
-        for bucket in &self.buckets {
-            match bucket {
-                None => {},
-                Some(bucket) => {
-                    match self.compute_insertable_index(bucket.hashed_key, &new_buckets) {
-                        None => {
-                            return None;
-                        },
-                        Some(idx) => {
-                            let cloned = bucket.clone();
-                            new_buckets[idx] = Some(Bucket{
-                                key: cloned.key,
-                                hashed_key: cloned.hashed_key,
-                                value: cloned.value,
-                                deleted: false,
-                            })
-                        }
+    fn rehash(&mut self) {
+        let next_len = self.next_capacity(self.capacity());
+        self.rehash_to(next_len);
+    }
+
+    // moves the entries into next_len buckets without hashing their keys again, growing further
+    // while some of them cannot be placed, or probing further while more of them share a hash than fit
+    fn rehash_to(&mut self, mut next_len: usize) {
+        let mut buckets = mem::take(&mut self.buckets);
+        // tombstones are dropped so that only counted entries survive
+        let mut entries: Vec<Bucket<K, V>> = buckets
+            .iter_mut()
+            .filter_map(|slot| mem::replace(slot, Slot::Empty).into_bucket())
+            .collect();
+        self.tombstones = 0;
+
+        // reuse the emptied buckets unless the capacity changes
+        if buckets.len() != next_len {
+            buckets = Self::make_empty_buckets(next_len);
+        }
+        loop {
+            match self.make_rehashed_buckets(buckets, entries) {
+                Err(returned) => {
+                    entries = returned;
+                    let shared = Self::count_most_sharing_a_hash(&entries);
+                    if shared > self.max_probe {
+                        self.max_probe = cmp::max(self.max_probe * 2, shared);
+                    } else {
+                        next_len = self.next_capacity(next_len);
                     }
+                    buckets = Self::make_empty_buckets(next_len);
+                },
+                Ok(new_buckets) => {
+                    self.buckets = new_buckets;
+                    return;
                 }
             }
         }
-        Some(new_buckets)
     }
 
-    pub fn delete(&mut self, key: &str) {
-        let hashed_key = self.compute_hash(key);
-        let idx = self.compute_bucket_index(hashed_key, self.len());
-        
-        for i in idx..cmp::min(idx + Self::MAX_PROBE, self.len()) {
-            match &mut self.buckets[i] {
-                // do nothing when reach empty bucket
-                None => { return; },
-                // delete value when reach non empty bucket and hashed key is identical
-                Some(bucket) if bucket.hashed_key == hashed_key => {
-                    bucket.deleted = true;
+    // returns the largest number of entries sharing one hash
+    fn count_most_sharing_a_hash(entries: &[Bucket<K, V>]) -> usize {
+        let mut hashes: Vec<u64> = entries.iter().map(|bucket| bucket.hashed_key).collect();
+        hashes.sort_unstable();
+        hashes.chunk_by(|a, b| a == b).map(<[u64]>::len).max().unwrap_or(0)
+    }
+
+    // moves entries into the empty new_buckets by their cached hashes,
+    // or hands every entry back when some of them cannot be placed
+    fn make_rehashed_buckets(&self, mut new_buckets: Buckets<K, V>, entries: Vec<Bucket<K, V>>) -> Result<Buckets<K, V>, Vec<Bucket<K, V>>> {
+        let mut overflowed = Vec::new();
+
+        for bucket in entries {
+            // Robin Hood mode never runs out of buckets below the maximum load factor
+            if self.robin_hood {
+                let idx = self.compute_bucket_index(bucket.hashed_key, new_buckets.len());
+                self.shift_in(&mut new_buckets, idx, bucket);
+                continue;
+            }
+            // keys are known to be distinct, so the first empty bucket will do
+            let vacant_idx = self
+                .probe_sequence(bucket.hashed_key, new_buckets.len())
+                .find(|&idx| matches!(new_buckets[idx], Slot::Empty));
+            match vacant_idx {
+                None => {
+                    overflowed.push(bucket);
                 },
-                // continue when reach non empty bucket but hashed key is not identical
-                Some(_) => {}
+                Some(idx) => {
+                    new_buckets[idx] = Slot::Occupied(bucket);
+                }
             }
         }
+
+        if overflowed.is_empty() {
+            Ok(new_buckets)
+        } else {
+            overflowed.extend(new_buckets.into_iter().filter_map(Slot::into_bucket));
+            Err(overflowed)
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Removes the entry stored for the key, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
 
-    #[test]
-    fn it_works() {
-        // Setup
-        let mut hash_table = HashTable::new();
-        
-        // Exercise: insert
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let value = i;
-            hash_table.upsert(key, value)
-        }
+    /// Removes the entry stored for the key, returning the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        let bucket = self.remove_at(idx);
+        Some((bucket.key, bucket.value))
+    }
 
-        // Verify: get (found)
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let expected_value = i;
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+    fn remove_at(&mut self, idx: usize) -> Bucket<K, V> {
+        if self.robin_hood {
+            return self.remove_at_shifting_back(idx);
         }
 
-        // Exercise: update
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let value = i * 2;
-            hash_table.upsert(key, value)
-        }
+        // leave a tombstone so that probe sequences passing over the bucket go on
+        let bucket = mem::replace(&mut self.buckets[idx], Slot::Deleted)
+            .into_bucket()
+            .expect("only live buckets are removed");
+        self.len -= 1;
+        self.tombstones += 1;
 
-        // Verify: update
-        for i in 1..100 {
-            let key = format!("key{}", i);
-            let expected_value = i * 2;
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+        // compact once tombstones pile up
+        if self.tombstones as f64 > Self::MAX_TOMBSTONE_RATIO * self.capacity() as f64 {
+            self.compact();
         }
+        bucket
+    }
 
-        // Verify: get (not found)
-        {
-            let actual = hash_table.get("key100");
-            assert!(actual.is_none());
-        }
+    // removes the entry in Robin Hood mode, moving each following entry
+    // one bucket back until an empty bucket or an entry in its initial bucket
+    fn remove_at_shifting_back(&mut self, idx: usize) -> Bucket<K, V> {
+        let bucket = mem::replace(&mut self.buckets[idx], Slot::Empty)
+            .into_bucket()
+            .expect("only live buckets are removed");
+        self.len -= 1;
 
-        // Exercise: delete and get (not found)
-        for i in 1..50 {
-            let key = format!("key{}", i);
-            hash_table.delete(key.as_str());
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_none());
+        let len = self.capacity();
+        let mut i = idx;
+        loop {
+            let next = (i + 1) & (len - 1);
+            match &self.buckets[next] {
+                Slot::Occupied(following) if self.probe_distance(following.hashed_key, next, len) > 0 => {
+                    self.buckets.swap(i, next);
+                    i = next;
+                },
+                _ => return bucket,
+            }
         }
+    }
+}
 
-        // Exercise: insert and get (found)
-        for expected_value in 1..50 {
-            let key = format!("key{}", expected_value);
-            hash_table.upsert(key.clone(), expected_value);
-            let actual = hash_table.get(key.as_str());
-            assert!(actual.is_some());
-            assert_eq!(expected_value, actual.unwrap());
+impl<K, V, S, G, P> Default for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    G: GrowthPolicy + Default,
+    P: ProbeStrategy + Default,
+{
+    fn default() -> Self {
+        Self::with_parts(0, S::default(), G::default(), P::default())
+    }
+}
+
+impl<K, V, S, G, P> fmt::Debug for HashTable<K, V, S, G, P>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, S, G, P> PartialEq for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S, G, P> Eq for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+}
+
+impl<K, V, S, G, P> FromIterator<(K, V)> for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    G: GrowthPolicy + Default,
+    P: ProbeStrategy + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hash_table = Self::default();
+        hash_table.extend(iter);
+        hash_table
+    }
+}
+
+impl<K, V, S, G, P> Extend<(K, V)> for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
         }
     }
 }
+
+impl<'a, K, V, S, G, P> Extend<(&'a K, &'a V)> for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq + Copy,
+    V: Copy,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, S, G, P, Q> Index<&Q> for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    type Output = V;
+
+    /// Returns a reference to the value stored for the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not stored.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key is not stored in the table")
+    }
+}
+
+impl<'a, K, V, S, G, P> IntoIterator for &'a HashTable<K, V, S, G, P> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        Iter { buckets: self.buckets.iter(), remaining: self.len }
+    }
+}
+
+impl<'a, K, V, S, G, P> IntoIterator for &'a mut HashTable<K, V, S, G, P> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        IterMut { buckets: self.buckets.iter_mut(), remaining: self.len }
+    }
+}
+
+impl<K, V, S, G, P> Map<K, V> for HashTable<K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    type Iter<'a> = Iter<'a, K, V> where Self: 'a, K: 'a, V: 'a;
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove(key)
+    }
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn iter(&self) -> Iter<'_, K, V> {
+        self.iter()
+    }
+}
+
+impl<K, V, S, G, P> IntoIterator for HashTable<K, V, S, G, P> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consumes the table into an iterator over the stored entries in bucket order.
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { buckets: self.buckets.into_iter(), remaining: self.len }
+    }
+}
+
+/// Iterator over the entries of a [`HashTable`], created by [`HashTable::iter`].
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Slot<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // skip empty buckets and tombstones
+        let bucket = self.buckets.find_map(Slot::bucket)?;
+        self.remaining -= 1;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Iterator over the entries of a [`HashTable`] with mutable references to the values,
+/// created by [`HashTable::iter_mut`].
+pub struct IterMut<'a, K, V> {
+    buckets: slice::IterMut<'a, Slot<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // skip empty buckets and tombstones
+        let bucket = self.buckets.find_map(Slot::bucket_mut)?;
+        self.remaining -= 1;
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owning iterator over the entries of a [`HashTable`], created by [`HashTable::into_iter`].
+pub struct IntoIter<K, V> {
+    buckets: vec::IntoIter<Slot<K, V>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // skip empty buckets and tombstones
+        let bucket = self.buckets.find_map(Slot::into_bucket)?;
+        self.remaining -= 1;
+        Some((bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+/// Draining iterator over the entries of a [`HashTable`], created by [`HashTable::drain`].
+pub struct Drain<'a, K, V> {
+    inner: IntoIter<K, V>,
+    marker: PhantomData<&'a mut HashTable<K, V>>,
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+/// Iterator removing the entries of a [`HashTable`] that match a predicate,
+/// created by [`HashTable::extract_if`].
+pub struct ExtractIf<'a, K, V, S, G, P, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    table: &'a mut HashTable<K, V, S, G, P>,
+    idx: usize,
+    removed: usize,
+    pred: F,
+}
+
+impl<K, V, S, G, P, F> Iterator for ExtractIf<'_, K, V, S, G, P, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.table.capacity() {
+            let slot = &mut self.table.buckets[self.idx];
+            self.idx += 1;
+            if let Slot::Occupied(bucket) = slot {
+                if (self.pred)(&bucket.key, &mut bucket.value) {
+                    // leave a tombstone until the iterator is dropped,
+                    // so that the buckets stay put meanwhile
+                    let bucket = mem::replace(slot, Slot::Deleted).into_bucket()?;
+                    self.table.len -= 1;
+                    self.table.tombstones += 1;
+                    self.removed += 1;
+                    return Some((bucket.key, bucket.value));
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.table.len))
+    }
+}
+
+impl<K, V, S, G, P, F> Drop for ExtractIf<'_, K, V, S, G, P, F>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    fn drop(&mut self) {
+        // rearrange the remaining entries instead of leaving tombstones
+        if self.removed > 0 {
+            self.table.compact();
+        }
+    }
+}
+
+/// Iterator over the keys of a [`HashTable`], created by [`HashTable::keys`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// Iterator over the values of a [`HashTable`], created by [`HashTable::values`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// Iterator over mutable references to the values of a [`HashTable`],
+/// created by [`HashTable::values_mut`].
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+/// Owning iterator over the keys of a [`HashTable`], created by [`HashTable::into_keys`].
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+/// Owning iterator over the values of a [`HashTable`], created by [`HashTable::into_values`].
+pub struct IntoValues<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+/// View into a single entry of a [`HashTable`], created by [`HashTable::entry`].
+pub enum Entry<'a, K, V, S = DefaultHashBuilder, G = Doubling, P = Linear> {
+    /// The key is stored in the table.
+    Occupied(OccupiedEntry<'a, K, V, S, G, P>),
+    /// The key is not stored in the table.
+    Vacant(VacantEntry<'a, K, V, S, G, P>),
+}
+
+/// View into an entry whose key is stored in a [`HashTable`].
+pub struct OccupiedEntry<'a, K, V, S = DefaultHashBuilder, G = Doubling, P = Linear> {
+    table: &'a mut HashTable<K, V, S, G, P>,
+    idx: usize,
+}
+
+/// View into an entry whose key is not stored in a [`HashTable`] yet.
+pub struct VacantEntry<'a, K, V, S = DefaultHashBuilder, G = Doubling, P = Linear> {
+    table: &'a mut HashTable<K, V, S, G, P>,
+    key: K,
+    hashed_key: u64,
+    idx: usize,
+}
+
+impl<'a, K, V, S, G, P> Entry<'a, K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the stored value, inserting `default` if the key is not stored.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the stored value, inserting the result of `default` if the key is not stored.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Returns the stored value, inserting the result of `default` for the key
+    /// if the key is not stored.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns the stored value, inserting `V::default()` if the key is not stored.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Applies `f` to the stored value if the key is stored.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Stores the value whether the key is stored or not.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S, G, P> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            },
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+}
+
+impl<'a, K, V, S, G, P> OccupiedEntry<'a, K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    fn bucket(&self) -> &Bucket<K, V> {
+        self.table.buckets[self.idx].bucket().expect("occupied entry points at a live bucket")
+    }
+
+    fn bucket_mut(&mut self) -> &mut Bucket<K, V> {
+        self.table.buckets[self.idx].bucket_mut().expect("occupied entry points at a live bucket")
+    }
+
+    /// Returns the stored key.
+    pub fn key(&self) -> &K {
+        &self.bucket().key
+    }
+
+    /// Returns a reference to the stored value.
+    pub fn get(&self) -> &V {
+        &self.bucket().value
+    }
+
+    /// Returns a mutable reference to the stored value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.bucket_mut().value
+    }
+
+    /// Converts the entry into a mutable reference to the stored value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.buckets[self.idx].bucket_mut().expect("occupied entry points at a live bucket").value
+    }
+
+    /// Replaces the stored value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry, returning the stored key and its value.
+    pub fn remove_entry(self) -> (K, V) {
+        let bucket = self.table.remove_at(self.idx);
+        (bucket.key, bucket.value)
+    }
+}
+
+impl<'a, K, V, S, G, P> VacantEntry<'a, K, V, S, G, P>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: GrowthPolicy,
+    P: ProbeStrategy,
+{
+    /// Returns the key that would be stored.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back the key without storing anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Stores the value for the key, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    /// Stores the value for the key, returning the now occupied entry.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S, G, P> {
+        let table = self.table;
+        let bucket = Bucket {
+            key: self.key,
+            hashed_key: self.hashed_key,
+            value,
+        };
+        if table.robin_hood {
+            // the entry stays at idx while the entries it displaces move on,
+            // with the buckets taken out as shift_in reads the table
+            let mut buckets = mem::take(&mut table.buckets);
+            table.shift_in(&mut buckets, self.idx, bucket);
+            table.buckets = buckets;
+        } else {
+            // reuse the tombstone
+            if let Slot::Deleted = table.buckets[self.idx] {
+                table.tombstones -= 1;
+            }
+            table.buckets[self.idx] = Slot::Occupied(bucket);
+        }
+        table.len += 1;
+        OccupiedEntry { table, idx: self.idx }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{shared_tests, CollidingKey, CountingHashBuilder, IdentityHasher};
+    use std::hash::BuildHasherDefault;
+    use rand::prelude::*;
+    use std::collections::HashMap;
+
+    // checks that bulk removal rearranged the entries instead of leaving tombstones
+    fn assert_no_tombstones<K, V, S, G, P>(hash_table: &HashTable<K, V, S, G, P>) {
+        assert_eq!(0, hash_table.tombstones);
+        assert!(hash_table.buckets.iter().all(|slot| !matches!(slot, Slot::Deleted)));
+    }
+
+    shared_tests!(assert_no_tombstones);
+
+    #[test]
+    fn colliding_hashes_keep_keys_apart() {
+        // Setup: no more colliding keys than a single probe sequence can hold
+        let mut hash_table = HashTable::new();
+        let keys = HashTable::<CollidingKey, u32>::DEFAULT_MAX_PROBE as u32;
+
+        // Exercise: insert keys whose hashes are identical
+        for i in 0..keys {
+            hash_table.insert(CollidingKey(i), i);
+        }
+
+        // Verify: each key keeps its own value
+        for i in 0..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+        assert!(hash_table.get(&CollidingKey(keys)).is_none());
+
+        // Exercise: update and delete single keys
+        hash_table.insert(CollidingKey(0), 100);
+        hash_table.remove(&CollidingKey(1));
+
+        // Verify: other keys are untouched
+        assert_eq!(Some(&100), hash_table.get(&CollidingKey(0)));
+        assert!(hash_table.get(&CollidingKey(1)).is_none());
+        for i in 2..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+    }
+
+    #[test]
+    fn colliding_hashes_lengthen_the_probe() {
+        // Setup: more colliding keys than a single probe sequence can hold
+        let mut hash_table = HashTable::new();
+        let max_probe = HashTable::<CollidingKey, u32>::DEFAULT_MAX_PROBE;
+        let keys = 4 * max_probe as u32 + 1;
+
+        // Exercise: insert keys whose hashes are identical
+        for i in 0..keys {
+            hash_table.insert(CollidingKey(i), i);
+        }
+
+        // Verify: the table probed further instead of growing without end
+        assert!(hash_table.max_probe() >= keys as usize);
+        assert!(hash_table.load_factor() > hash_table.max_load_factor() / 2.0);
+        for i in 0..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+
+        // Exercise: shorten the probe below the number of colliding keys
+        hash_table.set_max_probe(max_probe);
+
+        // Verify: rehashing lengthened it again to fit the colliding keys
+        assert!(hash_table.max_probe() >= keys as usize);
+        assert!(hash_table.load_factor() > hash_table.max_load_factor() / 2.0);
+        for i in 0..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+    }
+
+    #[test]
+    fn len_counts_live_entries() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        assert!(hash_table.is_empty());
+        assert_eq!(HashTable::<String, i32>::INITIAL_SIZE, hash_table.capacity());
+
+        // Exercise: insert
+        for i in 0..100 {
+            hash_table.insert(format!("key{}", i), i);
+        }
+
+        // Verify: every insert is counted, growth keeps the count
+        assert_eq!(100, hash_table.len());
+        assert!(hash_table.capacity() >= 100);
+        assert_eq!(100.0 / hash_table.capacity() as f64, hash_table.load_factor());
+
+        // Exercise: overwrite
+        for i in 0..100 {
+            hash_table.insert(format!("key{}", i), i * 2);
+        }
+
+        // Verify: overwrites are not counted
+        assert_eq!(100, hash_table.len());
+
+        // Exercise: delete, twice for the same key and once for a missing key
+        for i in 0..40 {
+            hash_table.remove(format!("key{}", i).as_str());
+            hash_table.remove(format!("key{}", i).as_str());
+        }
+        hash_table.remove("key100");
+
+        // Verify: tombstones are not counted
+        assert_eq!(60, hash_table.len());
+        assert_eq!(60, hash_table.iter().count());
+
+        // Exercise: clear
+        hash_table.clear();
+
+        // Verify: nothing is counted
+        assert!(hash_table.is_empty());
+        assert_eq!(0.0, hash_table.load_factor());
+    }
+
+    #[test]
+    fn growth_is_deterministic() {
+        // Exercise: fill two tables with the same keys and hashes
+        let fill = || {
+            let mut hash_table = HashTable::with_seed(0x5eed);
+            let mut capacities = Vec::new();
+            for i in 0..1000 {
+                hash_table.insert(i, i);
+                capacities.push(hash_table.capacity());
+            }
+            capacities
+        };
+
+        // Verify: both tables grew at the same inserts to the same doubled sizes
+        let capacities = fill();
+        assert_eq!(capacities, fill());
+        for capacity in capacities {
+            assert_eq!(0, capacity % HashTable::<i32, i32>::INITIAL_SIZE);
+            assert!((capacity / HashTable::<i32, i32>::INITIAL_SIZE).is_power_of_two());
+        }
+    }
+
+    // grows faster than the default policy, keeping capacities powers of two
+    struct Quadrupling;
+
+    impl GrowthPolicy for Quadrupling {
+        fn next_capacity(&self, capacity: usize) -> usize {
+            capacity * 4
+        }
+    }
+
+    #[test]
+    fn custom_growth_policy() {
+        // Setup
+        let mut hash_table = HashTable::with_growth_policy(Quadrupling);
+
+        // Exercise: insert
+        for i in 0..1000 {
+            hash_table.insert(format!("key{}", i), i);
+        }
+
+        // Verify: capacity grew by the policy and every key is reachable
+        let growth = hash_table.capacity() / HashTable::<String, usize>::INITIAL_SIZE;
+        assert!(growth.is_power_of_two() && growth.trailing_zeros().is_multiple_of(2));
+        for i in 0..1000 {
+            assert_eq!(Some(&i), hash_table.get(format!("key{}", i).as_str()));
+        }
+    }
+
+    // grows by half, which strays from powers of two
+    struct GrowingByHalf;
+
+    impl GrowthPolicy for GrowingByHalf {
+        fn next_capacity(&self, capacity: usize) -> usize {
+            capacity * 3 / 2
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "growth policy must return a larger power of two")]
+    fn growth_policy_must_keep_powers_of_two() {
+        let mut hash_table = HashTable::with_growth_policy(GrowingByHalf);
+        for i in 0..100 {
+            hash_table.insert(i, i);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn power_of_two_growth_policy_doubles() {
+        // Setup
+        let mut hash_table = HashTable::with_growth_policy(PowerOfTwo);
+
+        // Exercise: insert
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: capacity stays a power of two and every key is reachable
+        assert!(hash_table.capacity().is_power_of_two());
+        for i in 0..1000 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+    }
+
+    #[test]
+    fn probing_wraps_around() {
+        // Setup: keys whose probe sequence starts at the last bucket
+        let mut hash_table = HashTable::new();
+        let capacity = hash_table.capacity();
+        let keys: Vec<u32> = (0..)
+            .filter(|key| hash_table.compute_bucket_index(hash_table.compute_hash(key), capacity) == capacity - 1)
+            .take(hash_table.max_probe())
+            .collect();
+
+        // Exercise: insert the keys
+        for &key in &keys {
+            hash_table.insert(key, key);
+        }
+
+        // Verify: the keys wrapped around instead of forcing a rehash
+        assert_eq!(capacity, hash_table.capacity());
+        for &key in &keys {
+            assert_eq!(Some(&key), hash_table.get(&key));
+        }
+
+        // Exercise: delete a key in the wrapped part of the sequence
+        hash_table.remove(&keys[keys.len() - 1]);
+
+        // Verify: only that key is gone
+        assert!(hash_table.get(&keys[keys.len() - 1]).is_none());
+        assert_eq!(keys.len() - 1, hash_table.len());
+    }
+
+    #[test]
+    fn configurable_probe_and_load_factor() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        for i in 0..100 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: the default load factor is respected
+        assert!(hash_table.load_factor() <= hash_table.max_load_factor());
+
+        // Exercise: lower the maximum load factor
+        hash_table.set_max_load_factor(0.25);
+
+        // Verify: the table grew right away
+        assert!(hash_table.load_factor() <= 0.25);
+        for i in 0..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+
+        // Exercise: shorten the probe length to a single bucket
+        hash_table.set_max_probe(1);
+
+        // Verify: every entry still sits where it can be found
+        assert_eq!(1, hash_table.max_probe());
+        for i in 0..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+        for i in 100..200 {
+            hash_table.insert(i, i);
+        }
+        for i in 0..200 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+    }
+
+    #[test]
+    fn tombstones_never_duplicate_keys() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+
+        for _ in 0..20 {
+            // Setup: a small key space so that keys are deleted and reinserted often
+            let mut hash_table = HashTable::new();
+            let mut expected = HashMap::new();
+
+            for _ in 0..2000 {
+                // Exercise: random inserts and removes
+                let key = rng.gen_range(0..64u32);
+                if rng.gen_bool(0.6) {
+                    let value = rng.gen::<u32>();
+                    hash_table.insert(key, value);
+                    expected.insert(key, value);
+                } else {
+                    hash_table.remove(&key);
+                    expected.remove(&key);
+                }
+
+                // Verify: the table agrees with the model
+                assert_eq!(expected.len(), hash_table.len());
+                assert_eq!(expected.get(&key), hash_table.get(&key));
+            }
+
+            // Verify: every key is stored at most once
+            let mut keys: Vec<u32> = hash_table.iter().map(|(key, _)| *key).collect();
+            keys.sort_unstable();
+            keys.dedup();
+            assert_eq!(expected.len(), keys.len());
+            for (key, value) in &expected {
+                assert_eq!(Some(value), hash_table.get(key));
+            }
+        }
+    }
+
+    #[test]
+    fn tombstones_are_counted_and_compacted() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        for i in 0..100 {
+            hash_table.insert(i, i);
+        }
+        let capacity = hash_table.capacity();
+
+        // Exercise: delete a few keys
+        for i in 0..10 {
+            hash_table.remove(&i);
+        }
+
+        // Verify: each delete left a tombstone
+        assert_eq!(10, hash_table.tombstones);
+
+        // Exercise: compact
+        hash_table.compact();
+
+        // Verify: tombstones are gone, the buckets are kept and entries are reachable
+        assert_eq!(0, hash_table.tombstones);
+        assert_eq!(capacity, hash_table.capacity());
+        assert!(hash_table.buckets.iter().all(|slot| !matches!(slot, Slot::Deleted)));
+        assert_eq!(90, hash_table.len());
+        for i in 10..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+
+        // Exercise: delete most keys
+        for i in 10..90 {
+            hash_table.remove(&i);
+            // Verify: tombstones never pile up past the threshold
+            assert!(hash_table.tombstones as f64 <= 0.25 * hash_table.capacity() as f64);
+        }
+        assert_eq!(10, hash_table.len());
+        for i in 90..100 {
+            assert_eq!(Some(&i), hash_table.get(&i));
+        }
+
+        // Exercise: reinsert into tombstones and grow
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: rehashing carried no tombstone over
+        assert_eq!(0, hash_table.tombstones);
+        assert!(hash_table.buckets.iter().all(|slot| !matches!(slot, Slot::Deleted)));
+        assert_eq!(1000, hash_table.len());
+    }
+
+    #[test]
+    fn custom_hasher() {
+        // Setup
+        let mut hash_table = HashTable::with_capacity_and_hasher(12, BuildHasherDefault::<IdentityHasher>::default());
+        let capacity = hash_table.capacity();
+
+        // Exercise
+        for key in (0..12u64).rev() {
+            hash_table.insert(key, key * 10);
+        }
+
+        // Verify: the table had room for every entry, and each key landed in its own bucket
+        assert_eq!(capacity, hash_table.capacity());
+        assert_eq!(5, hash_table.hasher().hash_one(5u64));
+        let keys: Vec<u64> = hash_table.keys().copied().collect();
+        let mut expected: Vec<u64> = (0..12).collect();
+        expected.sort_by_key(|&key| fibonacci_index(key, capacity));
+        assert_eq!(expected, keys);
+        assert_eq!(Some(&50), hash_table.get(&5));
+    }
+
+    #[test]
+    fn with_capacity_fits_the_load_factor() {
+        // Exercise
+        let hash_table: HashTable<u64, u64> = HashTable::with_capacity(1000);
+
+        // Verify
+        assert!(1000.0 <= hash_table.max_load_factor() * hash_table.capacity() as f64);
+        assert_eq!(HashTable::<u64, u64>::INITIAL_SIZE, HashTable::<u64, u64>::with_capacity(0).capacity());
+    }
+
+    #[test]
+    fn rehashing_reuses_cached_hashes() {
+        // Setup
+        let hash_builder = CountingHashBuilder::default();
+        let mut hash_table = HashTable::with_hasher(hash_builder.clone());
+
+        // Exercise: grow through several rehashes
+        for i in 0..1000 {
+            hash_table.insert(i, i);
+        }
+
+        // Verify: every key was hashed once, on insertion
+        assert!(hash_table.capacity() >= 1024);
+        assert_eq!(1000, hash_builder.hashes());
+
+        // Exercise: leave enough tombstones to compact, then rehash in every other way
+        for i in 0..500 {
+            hash_table.remove(&i);
+        }
+        hash_table.compact();
+        hash_table.set_max_probe(8);
+        hash_table.set_max_load_factor(0.25);
+        hash_table.retain(|key, _| key % 2 == 0);
+        hash_table.extract_if(|key, _| key % 3 == 0).for_each(drop);
+        let cloned = hash_table.clone();
+
+        // Verify: only the removals hashed their keys
+        assert_eq!(1500, hash_builder.hashes());
+        assert_eq!(167, cloned.len());
+    }
+
+    // checks that every entry is at most one bucket further from its initial bucket
+    // than the entry before it, which Robin Hood lookups rely on
+    fn assert_robin_hood_invariant<K: Hash + Eq, V>(hash_table: &HashTable<K, V>) {
+        let len = hash_table.capacity();
+        for (i, slot) in hash_table.buckets.iter().enumerate() {
+            assert!(!matches!(slot, Slot::Deleted));
+            if let Slot::Occupied(bucket) = slot {
+                let distance = hash_table.probe_distance(bucket.hashed_key, i, len);
+                if distance > 0 {
+                    let previous = hash_table.buckets[(i + len - 1) & (len - 1)]
+                        .bucket()
+                        .expect("entries away from their initial bucket follow another entry");
+                    assert!(hash_table.probe_distance(previous.hashed_key, (i + len - 1) & (len - 1), len) + 1 >= distance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn robin_hood_mode() {
+        // Setup
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let mut hash_table = HashTable::with_seed(0x5eed);
+        hash_table.set_robin_hood(true);
+        let mut model = HashMap::new();
+
+        // Exercise: interleave inserts and removals over a small key space
+        for _ in 0..10000 {
+            let key: u32 = rng.gen_range(0..500);
+            if rng.gen_bool(0.6) {
+                assert_eq!(model.insert(key, key * 2), hash_table.insert(key, key * 2));
+            } else {
+                assert_eq!(model.remove(&key), hash_table.remove(&key));
+            }
+        }
+
+        // Verify: the table matches the model, keeps the invariant and left no tombstones
+        assert!(hash_table.robin_hood());
+        assert_eq!(model.len(), hash_table.len());
+        assert_eq!(0, hash_table.tombstones);
+        for (key, value) in &model {
+            assert_eq!(Some(value), hash_table.get(key));
+        }
+        assert_robin_hood_invariant(&hash_table);
+
+        // Exercise: bulk removal, then leave Robin Hood mode
+        hash_table.retain(|key, _| key % 2 == 0);
+        model.retain(|key, _| key % 2 == 0);
+        assert_robin_hood_invariant(&hash_table);
+        hash_table.set_robin_hood(false);
+
+        // Verify: every entry survived the rearrangement
+        assert_eq!(model.len(), hash_table.len());
+        for (key, value) in &model {
+            assert_eq!(Some(value), hash_table.get(key));
+        }
+    }
+
+    #[test]
+    fn robin_hood_mode_has_no_probe_limit() {
+        // Setup
+        let mut hash_table = HashTable::new();
+        hash_table.set_robin_hood(true);
+        let capacity = hash_table.capacity();
+        let keys = (hash_table.max_load_factor() * capacity as f64) as u32;
+
+        // Exercise: insert far more colliding keys than the maximum probe length
+        for i in 0..keys {
+            hash_table.entry(CollidingKey(i)).or_insert(i);
+        }
+
+        // Verify: the load factor alone decided against rehashing
+        assert!(keys as usize > 2 * hash_table.max_probe());
+        assert_eq!(capacity, hash_table.capacity());
+        for i in 0..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+
+        // Exercise & Verify: removals shift the following keys back
+        assert_eq!(Some(0), hash_table.remove(&CollidingKey(0)));
+        assert!(hash_table.buckets.iter().all(|slot| !matches!(slot, Slot::Deleted)));
+        for i in 1..keys {
+            assert_eq!(Some(&i), hash_table.get(&CollidingKey(i)));
+        }
+    }
+
+    #[test]
+    fn probe_strategies_cover_every_bucket() {
+        // Setup
+        let capacity = 64;
+        let strategies: [&dyn ProbeStrategy; 3] = [&Linear, &Quadratic, &DoubleHashing];
+
+        for probe_strategy in strategies {
+            for hashed_key in [0, 1, 0x9e37_79b9_7f4a_7c15, u64::MAX] {
+                // Exercise
+                let mut probed: Vec<usize> = (0..capacity)
+                    .map(|i| probe_strategy.offset(hashed_key, i, capacity) & (capacity - 1))
+                    .collect();
+
+                // Verify: a full probe sequence visits each bucket once
+                probed.sort();
+                probed.dedup();
+                assert_eq!(capacity, probed.len());
+            }
+        }
+    }
+
+    #[test]
+    fn quadratic_offsets_wrap_instead_of_overflowing() {
+        let capacity = 1 << 16;
+        for i in [1 << (usize::BITS / 2), usize::MAX / 2, usize::MAX - 1] {
+            // Exercise: probe counts whose triangular numbers overflow
+            let offset = Quadratic.offset(0, i, capacity) & (capacity - 1);
+
+            // Verify: the offset is still the triangular number modulo the capacity
+            let expected = (i as u128 * (i as u128 + 1) / 2) % capacity as u128;
+            assert_eq!(expected as usize, offset);
+        }
+    }
+
+    #[test]
+    fn double_hashing_steps_are_unrelated_to_the_low_bits() {
+        // Setup: hashes sharing their low bits, and so their initial bucket under a modulo
+        let capacity = 64;
+        let hashes = (1..=8).map(|k| 7 + k * capacity as u64);
+
+        // Exercise
+        let mut steps: Vec<usize> = hashes.map(|hashed_key| DoubleHashing.offset(hashed_key, 1, capacity) & (capacity - 1)).collect();
+
+        // Verify: the keys do not all follow one probe sequence
+        steps.sort();
+        steps.dedup();
+        assert!(steps.len() > 1);
+    }
+
+    fn exercise_probe_strategy<P: ProbeStrategy>(probe_strategy: P) {
+        // Setup
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let mut hash_table = HashTable::with_parts(0, DefaultHashBuilder::with_seed(0x5eed), Doubling, probe_strategy);
+        let mut model = HashMap::new();
+
+        // Exercise: interleave inserts and removals over a small key space
+        for _ in 0..10000 {
+            let key: u32 = rng.gen_range(0..500);
+            if rng.gen_bool(0.6) {
+                assert_eq!(model.insert(key, key * 2), hash_table.insert(key, key * 2));
+            } else {
+                assert_eq!(model.remove(&key), hash_table.remove(&key));
+            }
+        }
+
+        // Verify: the table matches the model
+        assert_eq!(model.len(), hash_table.len());
+        for (key, value) in &model {
+            assert_eq!(Some(value), hash_table.get(key));
+        }
+        for key in 500..600 {
+            assert!(!hash_table.contains_key(&key));
+        }
+    }
+
+    #[test]
+    fn probe_strategies() {
+        exercise_probe_strategy(Linear);
+        exercise_probe_strategy(Quadratic);
+        exercise_probe_strategy(DoubleHashing);
+
+        // Verify: the strategy constructor probes as told
+        let mut hash_table = HashTable::with_probe_strategy(Quadratic);
+        hash_table.insert("key1", 100);
+        assert_eq!(Some(&100), hash_table.get("key1"));
+    }
+}
@@ -0,0 +1,19 @@
+//! Re-exports of the hash tables under distinguishable names.
+//!
+//! ```
+//! use hashtable_rs::prelude::*;
+//!
+//! let mut open = OpenHashTable::new();
+//! let mut chained = ChainedHashTable::new();
+//! let mut cuckoo = CuckooHashTable::new();
+//! open.insert("key1", 100);
+//! chained.insert("key1", 100);
+//! cuckoo.insert("key1", 100);
+//! assert_eq!(open.get("key1"), chained.get("key1"));
+//! assert_eq!(open.get("key1"), cuckoo.get("key1"));
+//! ```
+
+pub use crate::closedaddressing::HashTable as ChainedHashTable;
+pub use crate::cuckoo::HashTable as CuckooHashTable;
+pub use crate::map::Map;
+pub use crate::openaddressing::HashTable as OpenHashTable;
@@ -0,0 +1,486 @@
+//! Fixtures shared by the tests of the hash tables.
+
+use std::cell::Cell;
+use std::hash::{BuildHasher, DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+
+// every key hashes to the same value, so all of them collide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CollidingKey(pub(crate) u32);
+
+impl Hash for CollidingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        0u32.hash(state);
+    }
+}
+
+// hashes integer keys to themselves, so their buckets are predictable
+#[derive(Default)]
+pub(crate) struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+// builds hashers that count how many keys they hash
+#[derive(Clone, Default)]
+pub(crate) struct CountingHashBuilder(Rc<Cell<usize>>);
+
+impl CountingHashBuilder {
+    pub(crate) fn hashes(&self) -> usize {
+        self.0.get()
+    }
+}
+
+impl BuildHasher for CountingHashBuilder {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        self.0.set(self.0.get() + 1);
+        DefaultHasher::new()
+    }
+}
+
+// generates the tests of the behavior every table shares, against the `HashTable`
+// and `Entry` in scope where it is invoked; bulk removal tests also run the given
+// check on the table after each removal
+macro_rules! shared_tests {
+    () => {
+        $crate::testing::shared_tests!(|_| {});
+    };
+    ($assert_after_removal:expr) => {
+        #[test]
+        fn it_works() {
+            // Setup
+            let mut hash_table = HashTable::new();
+
+            // Exercise: insert
+            for i in 1..100 {
+                let key = format!("key{}", i);
+                let value = i;
+                hash_table.insert(key, value);
+            }
+
+            // Verify: get (found)
+            for i in 1..100 {
+                let key = format!("key{}", i);
+                let expected_value = i;
+                let actual = hash_table.get(key.as_str());
+                assert!(actual.is_some());
+                assert_eq!(expected_value, *actual.unwrap());
+            }
+
+            // Exercise: update
+            for i in 1..100 {
+                let key = format!("key{}", i);
+                let value = i * 2;
+                hash_table.insert(key, value);
+            }
+
+            // Verify: update
+            for i in 1..100 {
+                let key = format!("key{}", i);
+                let expected_value = i * 2;
+                let actual = hash_table.get(key.as_str());
+                assert!(actual.is_some());
+                assert_eq!(expected_value, *actual.unwrap());
+            }
+
+            // Verify: get (not found)
+            {
+                let actual = hash_table.get("key100");
+                assert!(actual.is_none());
+            }
+
+            // Exercise: delete and get (not found)
+            for i in 1..50 {
+                let key = format!("key{}", i);
+                hash_table.remove(key.as_str());
+                let actual = hash_table.get(key.as_str());
+                assert!(actual.is_none());
+            }
+
+            // Exercise: insert and get (found)
+            for expected_value in 1..50 {
+                let key = format!("key{}", expected_value);
+                hash_table.insert(key.clone(), expected_value);
+                let actual = hash_table.get(key.as_str());
+                assert!(actual.is_some());
+                assert_eq!(expected_value, *actual.unwrap());
+            }
+        }
+
+        #[test]
+        fn generic_key_and_value() {
+            #[derive(Debug, PartialEq)]
+            struct Record {
+                name: String,
+                payload: Vec<u8>,
+            }
+
+            // Setup
+            let mut hash_table: HashTable<u64, Record> = HashTable::new();
+
+            // Exercise: insert
+            for id in 0..100u64 {
+                let record = Record { name: format!("record{}", id), payload: vec![id as u8; 4] };
+                hash_table.insert(id, record);
+            }
+
+            // Verify: get (found)
+            for id in 0..100u64 {
+                let actual = hash_table.get(&id).unwrap();
+                assert_eq!(format!("record{}", id), actual.name);
+                assert_eq!(vec![id as u8; 4], actual.payload);
+            }
+
+            // Verify: byte slices are looked up through Borrow
+            let mut by_bytes: HashTable<Vec<u8>, usize> = HashTable::new();
+            by_bytes.insert(b"alpha".to_vec(), 1);
+            assert_eq!(Some(&1), by_bytes.get(&b"alpha"[..]));
+            assert!(by_bytes.get(&b"beta"[..]).is_none());
+        }
+
+        #[test]
+        fn insert_and_remove_report_values() {
+            // Setup
+            let mut hash_table = HashTable::new();
+
+            // Exercise & Verify: a new key replaces nothing
+            assert_eq!(None, hash_table.insert("key1".to_string(), 1));
+
+            // Exercise & Verify: an existing key hands back the replaced value
+            assert_eq!(Some(1), hash_table.insert("key1".to_string(), 2));
+            assert_eq!(1, hash_table.len());
+
+            // Exercise & Verify: remove hands back the value once
+            assert_eq!(Some(2), hash_table.remove("key1"));
+            assert_eq!(None, hash_table.remove("key1"));
+
+            // Exercise & Verify: remove_entry hands back the stored key too
+            hash_table.insert("key2".to_string(), 3);
+            assert_eq!(Some(("key2".to_string(), 3)), hash_table.remove_entry("key2"));
+            assert_eq!(None, hash_table.remove_entry("key2"));
+            assert!(hash_table.is_empty());
+        }
+
+        #[test]
+        fn entry_api() {
+            // Setup
+            let mut hash_table: HashTable<String, usize> = HashTable::new();
+
+            // Exercise: count words through entries
+            for word in "a b a c b a".split_whitespace() {
+                *hash_table.entry(word.to_string()).or_default() += 1;
+            }
+
+            // Verify: counts
+            assert_eq!(Some(&3), hash_table.get("a"));
+            assert_eq!(Some(&2), hash_table.get("b"));
+            assert_eq!(Some(&1), hash_table.get("c"));
+            assert_eq!(3, hash_table.len());
+
+            // Exercise & Verify: and_modify only touches stored keys
+            hash_table.entry("a".to_string()).and_modify(|count| *count *= 10).or_insert(0);
+            hash_table.entry("d".to_string()).and_modify(|count| *count *= 10).or_insert(7);
+            assert_eq!(Some(&30), hash_table.get("a"));
+            assert_eq!(Some(&7), hash_table.get("d"));
+
+            // Exercise & Verify: or_insert_with and or_insert_with_key run only for missing keys
+            hash_table.entry("a".to_string()).or_insert_with(|| unreachable!());
+            assert_eq!(1, *hash_table.entry("e".to_string()).or_insert_with_key(|key| key.len()));
+
+            // Exercise & Verify: occupied entries
+            match hash_table.entry("b".to_string()) {
+                Entry::Occupied(mut entry) => {
+                    assert_eq!("b", entry.key());
+                    assert_eq!(2, entry.insert(20));
+                    assert_eq!(&20, entry.get());
+                    assert_eq!(("b".to_string(), 20), entry.remove_entry());
+                },
+                Entry::Vacant(_) => panic!("b is stored"),
+            }
+            assert!(!hash_table.contains_key("b"));
+
+            // Exercise & Verify: vacant entries
+            match hash_table.entry("f".to_string()) {
+                Entry::Occupied(_) => panic!("f is not stored"),
+                Entry::Vacant(entry) => {
+                    assert_eq!("f", entry.key());
+                    assert_eq!("f", entry.into_key());
+                },
+            }
+            assert!(!hash_table.contains_key("f"));
+
+            // Exercise & Verify: insert_entry stores either way
+            let entry = hash_table.entry("g".to_string()).insert_entry(1);
+            assert_eq!(1, entry.remove());
+            let entry = hash_table.entry("a".to_string()).insert_entry(1);
+            assert_eq!(&1, entry.get());
+            assert_eq!(4, hash_table.len());
+
+            // Exercise: many entries through growth
+            for i in 0..1000 {
+                *hash_table.entry(format!("key{}", i)).or_insert(0) += i;
+            }
+
+            // Verify: every entry is reachable
+            for i in 0..1000 {
+                assert_eq!(Some(&i), hash_table.get(format!("key{}", i).as_str()));
+            }
+            assert_eq!(1004, hash_table.len());
+        }
+
+        #[test]
+        fn mutable_access() {
+            // Setup: values too large to copy around casually
+            let mut hash_table: HashTable<String, Vec<u32>> = HashTable::new();
+            for i in 0..100 {
+                hash_table.insert(format!("key{}", i), vec![i]);
+            }
+
+            // Exercise & Verify: get_mut and update mutate in place
+            hash_table.get_mut("key1").unwrap().push(100);
+            assert!(hash_table.update("key2", |values| values.push(200)));
+            assert!(!hash_table.update("key100", |_| unreachable!()));
+            assert_eq!(Some(&vec![1, 100]), hash_table.get("key1"));
+            assert_eq!(Some(&vec![2, 200]), hash_table.get("key2"));
+
+            // Exercise & Verify: get_key_value hands back the stored key
+            let (key, values) = hash_table.get_key_value("key3").unwrap();
+            assert_eq!("key3", key);
+            assert_eq!(&vec![3], values);
+            assert!(hash_table.get_key_value("key100").is_none());
+
+            // Exercise: get_many_mut over disjoint keys
+            let keys: Vec<String> = (0..100).map(|i| format!("key{}", i)).collect();
+            for chunk in keys.chunks(4) {
+                let [a, b, c, d] = hash_table
+                    .get_many_mut([chunk[3].as_str(), chunk[0].as_str(), chunk[2].as_str(), chunk[1].as_str()])
+                    .unwrap();
+                a.push(3);
+                b.push(0);
+                c.push(2);
+                d.push(1);
+            }
+
+            // Verify: each value got its own push
+            for (i, key) in keys.iter().enumerate() {
+                assert_eq!(Some(&((i % 4) as u32)), hash_table.get(key.as_str()).unwrap().last());
+            }
+
+            // Verify: duplicate or missing keys are refused
+            assert!(hash_table.get_many_mut(["key1", "key1"]).is_none());
+            assert!(hash_table.get_many_mut(["key1", "key100"]).is_none());
+        }
+
+        #[test]
+        fn iterators() {
+            // Setup: a table with removed entries
+            let mut hash_table = HashTable::new();
+            for i in 0..100 {
+                hash_table.insert(i, i * 10);
+            }
+            for i in 0..100 {
+                if i % 3 == 0 {
+                    hash_table.remove(&i);
+                }
+            }
+            let expected_keys: Vec<i32> = (0..100).filter(|i| i % 3 != 0).collect();
+            let sorted = |mut items: Vec<i32>| {
+                items.sort_unstable();
+                items
+            };
+
+            // Verify: borrowing iterators see each live entry once
+            assert_eq!(expected_keys.len(), hash_table.iter().len());
+            assert_eq!(expected_keys, sorted(hash_table.keys().copied().collect()));
+            assert_eq!(
+                expected_keys.iter().map(|i| i * 10).collect::<Vec<_>>(),
+                sorted(hash_table.values().copied().collect())
+            );
+            assert!(hash_table.iter().all(|(key, value)| *value == key * 10));
+
+            // Exercise: mutate through iter_mut and values_mut
+            for (key, value) in hash_table.iter_mut() {
+                *value += key;
+            }
+            for value in hash_table.values_mut() {
+                *value += 1;
+            }
+
+            // Verify: the values were mutated in place
+            for &key in &expected_keys {
+                assert_eq!(Some(&(key * 11 + 1)), hash_table.get(&key));
+            }
+
+            // Verify: owning iterators hand out every live entry once
+            let mut entries: Vec<(i32, i32)> = hash_table.into_iter().collect();
+            entries.sort_unstable();
+            assert_eq!(expected_keys.iter().map(|&i| (i, i * 11 + 1)).collect::<Vec<_>>(), entries);
+
+            let hash_table: HashTable<i32, i32> = {
+                let mut hash_table = HashTable::new();
+                for &key in &expected_keys {
+                    hash_table.insert(key, -key);
+                }
+                hash_table
+            };
+            let into_keys = hash_table.into_keys();
+            assert_eq!(expected_keys.len(), into_keys.len());
+            assert_eq!(expected_keys, sorted(into_keys.collect()));
+
+            let mut hash_table = HashTable::new();
+            hash_table.insert("key", 1);
+            assert_eq!(vec![1], hash_table.into_values().collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn bulk_removal() {
+            let fill = || {
+                let mut hash_table = HashTable::new();
+                for i in 0..1000 {
+                    hash_table.insert(i, i);
+                }
+                hash_table
+            };
+
+            // Exercise: drain
+            let mut hash_table = fill();
+            let capacity = hash_table.capacity();
+            let mut drained: Vec<(i32, i32)> = hash_table.drain().collect();
+            drained.sort_unstable();
+
+            // Verify: every entry was drained and the table is empty but reusable
+            assert_eq!((0..1000).map(|i| (i, i)).collect::<Vec<_>>(), drained);
+            assert!(hash_table.is_empty());
+            assert_eq!(capacity, hash_table.capacity());
+            hash_table.insert(1, 1);
+            assert_eq!(Some(&1), hash_table.get(&1));
+
+            // Exercise: drop a drain without consuming it
+            hash_table.drain();
+
+            // Verify: the entries were removed anyway
+            assert!(hash_table.is_empty());
+            assert!(hash_table.get(&1).is_none());
+
+            // Exercise: retain even keys
+            let mut hash_table = fill();
+            hash_table.retain(|key, value| {
+                *value *= 2;
+                key % 2 == 0
+            });
+
+            // Verify: odd keys are gone, even keys were updated
+            assert_eq!(500, hash_table.len());
+            for i in 0..1000 {
+                assert_eq!((i % 2 == 0).then_some(i * 2), hash_table.get(&i).copied());
+            }
+            $assert_after_removal(&hash_table);
+
+            // Exercise: extract multiples of three
+            let mut extracted: Vec<(i32, i32)> = hash_table.extract_if(|key, _| key % 3 == 0).collect();
+            extracted.sort_unstable();
+
+            // Verify: exactly the matching entries were handed out
+            assert_eq!((0..1000).filter(|i| i % 6 == 0).map(|i| (i, i * 2)).collect::<Vec<_>>(), extracted);
+            assert_eq!(500 - extracted.len(), hash_table.len());
+            for (key, _) in &extracted {
+                assert!(hash_table.get(key).is_none());
+            }
+            $assert_after_removal(&hash_table);
+
+            // Exercise: extract lazily and stop early
+            let len = hash_table.len();
+            let taken = hash_table.extract_if(|_, _| true).take(3).count();
+
+            // Verify: only the taken entries were removed
+            assert_eq!(3, taken);
+            assert_eq!(len - 3, hash_table.len());
+            assert_eq!(len - 3, hash_table.iter().count());
+            $assert_after_removal(&hash_table);
+        }
+
+        #[test]
+        fn standard_traits() {
+            // Exercise: collect and extend
+            let mut hash_table: HashTable<String, i32> = (0..10).map(|i| (format!("key{}", i), i)).collect();
+            hash_table.extend((10..20).map(|i| (format!("key{}", i), i)));
+
+            // Verify: index and equality regardless of insertion order
+            assert_eq!(20, hash_table.len());
+            assert_eq!(5, hash_table["key5"]);
+            let reversed: HashTable<String, i32> = (0..20).rev().map(|i| (format!("key{}", i), i)).collect();
+            assert_eq!(hash_table, reversed);
+
+            // Exercise & Verify: clones are equal but independent
+            let mut cloned = hash_table.clone();
+            assert_eq!(hash_table, cloned);
+            cloned.insert("key0".to_string(), -1);
+            assert_ne!(hash_table, cloned);
+            cloned.remove("key0");
+            assert_ne!(hash_table, cloned);
+            assert_eq!(0, hash_table["key0"]);
+
+            // Exercise & Verify: iterate through references
+            for (_, value) in &mut hash_table {
+                *value += 1;
+            }
+            let mut sum = 0;
+            for (_, value) in &hash_table {
+                sum += value;
+            }
+            assert_eq!((1..=20).sum::<i32>(), sum);
+
+            // Exercise & Verify: default and extend from references
+            let mut copied: HashTable<i32, i32> = HashTable::default();
+            let source: HashTable<i32, i32> = [(1, 10), (2, 20)].into_iter().collect();
+            copied.extend(&source);
+            assert_eq!(source, copied);
+
+            // Verify: debug prints entries only, like a map
+            let mut single = HashTable::new();
+            single.insert("key", 1);
+            single.insert("removed", 2);
+            single.remove("removed");
+            assert_eq!(r#"{"key": 1}"#, format!("{:?}", single));
+        }
+
+        #[test]
+        #[should_panic]
+        fn index_panics_for_missing_keys() {
+            let hash_table: HashTable<String, i32> = HashTable::new();
+            let _ = hash_table["key"];
+        }
+
+        #[test]
+        fn seeded_tables_are_reproducible() {
+            // Exercise: fill two tables seeded alike
+            let fill = || {
+                let mut hash_table = HashTable::with_seed(0x5eed);
+                for i in 0..1000 {
+                    hash_table.insert(format!("key{}", i), i);
+                }
+                hash_table.keys().cloned().collect::<Vec<String>>()
+            };
+
+            // Verify: both tables laid out their keys in the same order
+            assert_eq!(fill(), fill());
+        }
+    };
+}
+
+pub(crate) use shared_tests;